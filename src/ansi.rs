@@ -0,0 +1,266 @@
+extern crate cannon;
+extern crate libc;
+
+use cannon::color;
+use std::io::{self, Read, Write};
+use std::mem;
+
+const ESCAPE: u8 = 27;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ConsoleSize{
+    pub width: i16,
+    pub height: i16
+}
+
+///A POSIX terminal driven with ANSI escape sequences. Mirrors the parts of
+///`cannon::Console` the game uses so the rest of the code does not care
+///which one it is talking to. Output is held back until `flush`.
+pub struct Console{
+    should_cls: bool,
+    cursor_x: i16,
+    cursor_y: i16,
+    pending: String
+}
+
+impl Console{
+    pub fn new() -> Console{
+        Console {should_cls: false, cursor_x: 0, cursor_y: 0, pending: String::new()}
+    }
+
+    ///When set the screen is cleared once the console is dropped
+    pub fn set_should_cls(&mut self, should_cls: bool){
+        self.should_cls = should_cls;
+    }
+
+    pub fn set_color(&mut self, foreground: u16, background: u16){
+        self.pending.push_str(&format!("\x1b[{};{}m", ansi_foreground(foreground), ansi_background(background)));
+    }
+
+    pub fn set_cursor_position(&mut self, x: i16, y: i16){
+        self.cursor_x = x;
+        self.cursor_y = y;
+        self.pending.push_str(&format!("\x1b[{};{}H", y + 1, x + 1));
+    }
+
+    ///Newlines behave like the windows console and return to the first column
+    pub fn write(&mut self, string: &str){
+        let mut lines = string.split('\n');
+
+        if let Some(first) = lines.next(){
+            self.cursor_x += first.chars().count() as i16;
+            self.pending.push_str(first);
+        }
+
+        for line in lines{
+            self.cursor_y += 1;
+            self.cursor_x = line.chars().count() as i16;
+            self.pending.push_str(&format!("\r\n{}", line));
+        }
+    }
+
+    pub fn write_character(&mut self, x: i16, y: i16, character: u8){
+        self.pending.push_str(&format!("\x1b[{};{}H{}", y + 1, x + 1, character as char));
+        self.pending.push_str(&format!("\x1b[{};{}H", self.cursor_y + 1, self.cursor_x + 1));
+    }
+
+    pub fn clear_screen(&mut self){
+        self.pending.push_str("\x1b[2J\x1b[H");
+        self.cursor_x = 0;
+        self.cursor_y = 0;
+    }
+
+    pub fn get_console_size(&self) -> ConsoleSize{
+        unsafe {
+            let mut size: libc::winsize = mem::zeroed();
+
+            if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col > 0{
+                ConsoleSize {width: size.ws_col as i16, height: size.ws_row as i16}
+            }else{
+                ConsoleSize {width: 80, height: 24}
+            }
+        }
+    }
+
+    pub fn flush(&mut self){
+        emit(&self.pending);
+        self.pending.clear();
+    }
+
+    ///Terminals are resized by the user, so this is a no-op
+    pub fn set_console_size(&mut self, _width: i16, _height: i16){}
+}
+
+impl Default for Console{
+    fn default() -> Console{
+        Console::new()
+    }
+}
+
+impl Drop for Console{
+    fn drop(&mut self){
+        if self.should_cls{
+            self.pending.push_str("\x1b[0m\x1b[2J\x1b[H");
+        }
+        self.flush();
+    }
+}
+
+///Puts the terminal into raw mode on the alternate screen. The previous
+///settings are restored when this is dropped.
+pub struct RawMode{
+    original: Option<libc::termios>
+}
+
+impl RawMode{
+    pub fn enable() -> RawMode{
+        let original = unsafe {
+            let mut termios: libc::termios = mem::zeroed();
+
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0{
+                let original = termios;
+                termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+                termios.c_iflag &= !(libc::IXON | libc::ICRNL);
+                termios.c_oflag &= !libc::OPOST;
+                termios.c_cc[libc::VMIN] = 1;
+                termios.c_cc[libc::VTIME] = 0;
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &termios);
+                Some(original)
+            }else{
+                None
+            }
+        };
+
+        emit("\x1b[?1049h\x1b[?25l");
+        RawMode {original: original}
+    }
+}
+
+impl Drop for RawMode{
+    fn drop(&mut self){
+        emit("\x1b[0m\x1b[?25h\x1b[?1049l");
+
+        if let Some(ref original) = self.original{
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, original);
+            }
+        }
+    }
+}
+
+///Blocks until at least one key is read from stdin and returns the windows
+///virtual key codes for everything that arrived
+pub fn read_key_codes() -> Vec<u32>{
+    let mut buffer = [0u8; 32];
+    let stdin = io::stdin();
+    let read = stdin.lock().read(&mut buffer).unwrap_or(0);
+
+    to_key_codes(&buffer[..read])
+}
+
+fn to_key_codes(bytes: &[u8]) -> Vec<u32>{
+    let mut codes = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len(){
+        if bytes[i] == ESCAPE{
+            if i + 2 < bytes.len() && (bytes[i + 1] == b'[' || bytes[i + 1] == b'O'){
+                match bytes[i + 2]{
+                    b'A' => codes.push(0x26),
+                    b'B' => codes.push(0x28),
+                    b'C' => codes.push(0x27),
+                    b'D' => codes.push(0x25),
+                    _ => (),
+                }
+                i += 3;
+            }else{
+                codes.push(0x1B);
+                i += 1;
+            }
+            continue;
+        }
+
+        let code = match bytes[i]{
+            b'\r' | b'\n' => Some(0x0D),
+            3 => Some(0x1B),//Ctrl-C
+            8 | 127 => Some(0x08),
+            b' ' => Some(0x20),
+            c @ b'0'..=b'9' => Some(c as u32),
+            c @ b'a'..=b'z' => Some(c.to_ascii_uppercase() as u32),
+            c @ b'A'..=b'Z' => Some(c as u32),
+            _ => None,
+        };
+
+        if let Some(code) = code{
+            codes.push(code);
+        }
+        i += 1;
+    }
+
+    codes
+}
+
+fn emit(string: &str){
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(string.as_bytes()).ok();
+    handle.flush().ok();
+}
+
+fn ansi_foreground(c: u16) -> u8{
+    ansi_color(c)
+}
+
+fn ansi_background(c: u16) -> u8{
+    ansi_color(c) + 10
+}
+
+///Maps the windows console palette used by `cannon::color` onto the 16 ANSI colors
+fn ansi_color(c: u16) -> u8{
+    match c{
+        color::BLACK => 30,
+        color::DARK_RED => 31,
+        color::DARK_GREEN => 32,
+        color::BROWN => 33,
+        color::DARK_BLUE => 34,
+        color::DARK_MAGENTA => 35,
+        color::DARK_CYAN => 36,
+        color::LIGHT_GRAY => 37,
+        color::DARK_GRAY => 90,
+        color::RED => 91,
+        color::GREEN => 92,
+        color::YELLOW => 93,
+        color::BLUE => 94,
+        color::MAGENTA => 95,
+        color::CYAN => 96,
+        _ => 97,
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn arrows_become_arrow_keys(){
+        assert_eq!(to_key_codes(b"\x1b[A\x1b[B\x1b[C\x1b[D"), vec![0x26, 0x28, 0x27, 0x25]);
+        assert_eq!(to_key_codes(b"\x1bOA"), vec![0x26]);
+    }
+
+    #[test]
+    fn a_lone_escape_is_escape(){
+        assert_eq!(to_key_codes(b"\x1b"), vec![0x1B]);
+        assert_eq!(to_key_codes(b"\x1bq"), vec![0x1B, 'Q' as u32]);
+    }
+
+    #[test]
+    fn enter_and_backspace(){
+        assert_eq!(to_key_codes(b"\r\n"), vec![0x0D, 0x0D]);
+        assert_eq!(to_key_codes(&[8, 127]), vec![0x08, 0x08]);
+    }
+
+    #[test]
+    fn letters_are_upper_case(){
+        assert_eq!(to_key_codes(b"wasdW"), vec!['W' as u32, 'A' as u32, 'S' as u32, 'D' as u32, 'W' as u32]);
+    }
+}
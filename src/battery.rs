@@ -0,0 +1,90 @@
+use std::cmp;
+
+///Capacity a magma hit crimples away for good
+const HIT_WEAR: i16 = 5;
+///However often it is hit, the battery keeps this much capacity
+const MIN_CAPACITY: i16 = 10;
+///Capacity an Upgrade adds, up to `MAX_CAPACITY`
+const UPGRADE_CAPACITY: i16 = 10;
+const MAX_CAPACITY: i16 = 100;
+
+///The player's battery. The game is over once it is empty.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Battery{
+    pub charge: i16,
+    ///The most charge it holds right now
+    pub capacity: i16,
+    ///Capacity magma has taken away over the game
+    pub lost: i16,
+    ///Charge used up every second
+    pub drain: i16
+}
+
+impl Battery{
+    pub fn new(charge: i16, capacity: i16, drain: i16) -> Battery{
+        Battery {charge: cmp::min(charge, capacity), capacity: capacity, lost: 0, drain: drain}
+    }
+
+    ///Charges it up, as far as the capacity allows
+    pub fn add(&mut self, charge: i16){
+        self.charge = cmp::min(self.charge + charge, self.capacity);
+    }
+
+    ///A second of use, `extra` on top of the usual drain
+    pub fn drain(&mut self, extra: i16){
+        self.charge -= self.drain + extra;
+    }
+
+    ///Magma halves the charge and crimples away some of the capacity
+    pub fn hit(&mut self){
+        let wear = (self.capacity - MIN_CAPACITY).clamp(0, HIT_WEAR);
+        self.capacity -= wear;
+        self.lost += wear;
+        self.charge = cmp::min(self.charge / 2, self.capacity);
+    }
+
+    pub fn upgrade(&mut self){
+        self.capacity = cmp::min(self.capacity + UPGRADE_CAPACITY, cmp::max(MAX_CAPACITY, self.capacity));
+    }
+
+    ///Percent of the capacity it has had that it still has
+    pub fn health(&self) -> i16{
+        (self.capacity as i32 * 100 / cmp::max(self.capacity + self.lost, 1) as i32) as i16
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.charge < 1
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn hits_wear_it_down(){
+        let mut battery = Battery::new(60, 70, 2);
+        battery.hit();
+        assert_eq!(battery.charge, 30);
+        assert_eq!(battery.capacity, 70 - HIT_WEAR);
+        assert_eq!(battery.health(), 92);
+
+        for _ in 0..20{
+            battery.hit();
+        }
+        assert_eq!(battery.capacity, MIN_CAPACITY);
+        battery.add(50);
+        assert_eq!(battery.charge, MIN_CAPACITY);
+    }
+
+    #[test]
+    fn upgrades_add_capacity(){
+        let mut battery = Battery::new(10, 95, 2);
+        battery.upgrade();
+        assert_eq!(battery.capacity, MAX_CAPACITY);
+
+        battery.drain(1);
+        assert_eq!(battery.charge, 7);
+        assert!(!battery.is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use cannon::input::Key;
+use input::{key_code, code_name};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action{
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Confirm,
+    Pause,
+    Restart,
+    Quit,
+    HighScores,
+    Continue,
+    Options
+}
+
+pub const ACTIONS: [Action; 11] = [Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight,
+    Action::Confirm, Action::Pause, Action::Restart, Action::Quit, Action::HighScores, Action::Continue,
+    Action::Options];
+
+impl Action{
+    ///The name used in config and replay files
+    pub fn name(&self) -> &'static str{
+        match *self{
+            Action::MoveUp => "MoveUp",
+            Action::MoveDown => "MoveDown",
+            Action::MoveLeft => "MoveLeft",
+            Action::MoveRight => "MoveRight",
+            Action::Confirm => "Confirm",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+            Action::Quit => "Quit",
+            Action::HighScores => "HighScores",
+            Action::Continue => "Continue",
+            Action::Options => "Options",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action>{
+        ACTIONS.iter().find(|action| action.name() == name).cloned()
+    }
+
+    pub fn label(&self) -> &'static str{
+        match *self{
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::HighScores => "High Scores",
+            _ => self.name(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Preset{
+    Arrows,
+    Wasd,
+    Hjkl
+}
+
+impl Preset{
+    pub fn from_name(name: &str) -> Option<Preset>{
+        match name{
+            "arrows" => Some(Preset::Arrows),
+            "wasd" => Some(Preset::Wasd),
+            "hjkl" => Some(Preset::Hjkl),
+            _ => None,
+        }
+    }
+}
+
+///Which keys trigger which actions. A key may trigger several actions,
+///the screen it is pressed on decides which one counts.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyMap{
+    bindings: Vec<(Action, Vec<u32>)>
+}
+
+impl KeyMap{
+    pub fn new() -> KeyMap{
+        KeyMap::preset(Preset::Arrows)
+    }
+
+    ///The arrow preset matches the original hardcoded controls, the others
+    ///add their movement keys on top of the arrows
+    pub fn preset(preset: Preset) -> KeyMap{
+        let (up, down, left, right) = match preset{
+            Preset::Arrows => (vec![0x26], vec![0x28], vec![0x25], vec![0x27]),
+            Preset::Wasd => (vec![code('W'), 0x26], vec![code('S'), 0x28], vec![code('A'), 0x25], vec![code('D'), 0x27]),
+            Preset::Hjkl => (vec![code('K'), 0x26], vec![code('J'), 0x28], vec![code('H'), 0x25], vec![code('L'), 0x27]),
+        };
+
+        KeyMap {bindings: vec![
+            (Action::MoveUp, up),
+            (Action::MoveDown, down),
+            (Action::MoveLeft, left),
+            (Action::MoveRight, right),
+            (Action::Confirm, vec![0x0D]),
+            (Action::Pause, vec![0x1B, code('P')]),
+            (Action::Restart, vec![code('R')]),
+            (Action::Quit, vec![0x1B, code('Q')]),
+            (Action::HighScores, vec![code('T')]),
+            (Action::Continue, vec![code('C')]),
+            (Action::Options, vec![code('O')]),
+        ]}
+    }
+
+    pub fn is(&self, action: Action, key: &Key) -> bool{
+        match key_code(key){
+            Some(code) => self.keys(action).contains(&code),
+            None => false,
+        }
+    }
+
+    ///The first action a key sends to a game being played, a move or
+    ///Confirm to put down a solar panel
+    pub fn play_action(&self, key: &Key) -> Option<Action>{
+        ACTIONS[..5].iter().find(|action| self.is(**action, key)).cloned()
+    }
+
+    pub fn keys(&self, action: Action) -> &[u32]{
+        self.bindings.iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, codes)| &codes[..])
+            .unwrap_or(&[])
+    }
+
+    pub fn bind(&mut self, action: Action, codes: Vec<u32>){
+        for binding in self.bindings.iter_mut(){
+            if binding.0 == action{
+                binding.1 = codes;
+                return;
+            }
+        }
+        self.bindings.push((action, codes));
+    }
+
+    ///The name of the first key bound to an action, for on screen hints
+    pub fn key_name(&self, action: Action) -> String{
+        self.keys(action).first().map(|code| code_name(*code)).unwrap_or_else(|| "?".to_string())
+    }
+
+    ///Every key bound to an action, separated by spaces
+    pub fn describe(&self, action: Action) -> String{
+        self.keys(action).iter().map(|code| code_name(*code)).collect::<Vec<_>>().join(" ")
+    }
+}
+
+impl Default for KeyMap{
+    fn default() -> KeyMap{
+        KeyMap::new()
+    }
+}
+
+fn code(letter: char) -> u32{
+    letter as u32
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn presets_give_each_menu_key_one_action(){
+        let menu = [Action::MoveLeft, Action::MoveRight, Action::Confirm, Action::Quit, Action::HighScores,
+            Action::Continue, Action::Options];
+
+        for preset in [Preset::Arrows, Preset::Wasd, Preset::Hjkl].iter(){
+            let keys = KeyMap::preset(*preset);
+            let mut codes: Vec<u32> = menu.iter().flat_map(|action| keys.keys(*action).to_vec()).collect();
+            let bound = codes.len();
+            codes.sort();
+            codes.dedup();
+
+            assert_eq!(codes.len(), bound, "{:?} binds a key to two menu actions", preset);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use rand::Rng;
+use bindings::Action;
+use random::GameRng;
+use simulation::Simulation;
+use Powerup;
+
+///Bots move at most this often, so they play at about human speed
+const MOVE_MILLIS: u64 = 120;
+
+const MOVES: [Action; 4] = [Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BotKind{
+    ///Stands still until the battery runs out
+    Idle,
+    ///Walks in random directions, stepping around ash
+    Wander,
+    ///Heads for the nearest charge, stepping around ash
+    Greedy
+}
+
+pub const BOTS: [BotKind; 3] = [BotKind::Idle, BotKind::Wander, BotKind::Greedy];
+
+impl BotKind{
+    pub fn name(&self) -> &'static str{
+        match *self{
+            BotKind::Idle => "idle",
+            BotKind::Wander => "wander",
+            BotKind::Greedy => "greedy",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BotKind>{
+        BOTS.iter().find(|bot| bot.name() == name).cloned()
+    }
+}
+
+///Plays the game in place of the keyboard. Seeded from the game, so the
+///same bot plays the same seed the same way.
+pub struct Bot{
+    kind: BotKind,
+    rng: GameRng,
+    next_move: u64
+}
+
+impl Bot{
+    pub fn new(kind: BotKind, seed: u64) -> Bot{
+        Bot {kind: kind, rng: GameRng::new(seed ^ 0x5eed), next_move: 0}
+    }
+
+    ///The bot's next move, if it is time for one
+    pub fn act(&mut self, sim: &Simulation) -> Option<Action>{
+        if sim.elapsed() < self.next_move{
+            return None;
+        }
+        self.next_move = sim.elapsed() + MOVE_MILLIS;
+
+        let safe: Vec<Action> = MOVES.iter().cloned().filter(|action| is_safe(sim, *action)).collect();
+        match self.kind{
+            BotKind::Idle => None,
+            BotKind::Wander => self.pick(&safe),
+            BotKind::Greedy => match nearest_charge(sim){
+                Some(target) => {
+                    let closer: Vec<Action> = safe.iter().cloned()
+                        .filter(|action| distance(step(sim, *action), target) < distance(position(sim), target))
+                        .collect();
+
+                    if closer.is_empty(){ self.pick(&safe) }else{ self.pick(&closer) }
+                },
+                None => self.pick(&safe),
+            },
+        }
+    }
+
+    fn pick(&mut self, actions: &[Action]) -> Option<Action>{
+        if actions.is_empty(){
+            None
+        }else{
+            Some(actions[self.rng.gen_range(0, actions.len())])
+        }
+    }
+}
+
+///The field cell the player stands on
+fn position(sim: &Simulation) -> (i16, i16){
+    (sim.game.player_pos.x, sim.game.player_pos.y - 1)
+}
+
+///The field cell a move would take the player to
+fn step(sim: &Simulation, action: Action) -> (i16, i16){
+    let (x, y) = position(sim);
+
+    match action{
+        Action::MoveUp => (x, y - 1),
+        Action::MoveDown => (x, y + 1),
+        Action::MoveLeft => (x - 1, y),
+        Action::MoveRight => (x + 1, y),
+        _ => (x, y),
+    }
+}
+
+///Ash that is about to land counts as ash already
+fn is_safe(sim: &Simulation, action: Action) -> bool{
+    let (x, y) = step(sim, action);
+    sim.game.items.contains(x, y) && sim.game.items.get(x, y) != Some(Powerup::Destroyed) &&
+        !sim.falling.iter().any(|falling| (falling.x, falling.y) == (x, y))
+}
+
+fn nearest_charge(sim: &Simulation) -> Option<(i16, i16)>{
+    let field = &sim.game.items;
+    let from = position(sim);
+
+    (0..field.width())
+        .flat_map(|x| (0..field.height()).map(move |y| (x, y)))
+        .filter(|&(x, y)| field.get(x, y) == Some(Powerup::Charge))
+        .min_by_key(|&cell| distance(cell, from))
+}
+
+fn distance(a: (i16, i16), b: (i16, i16)) -> i16{
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+#[cfg(test)]
+mod tests{
+    use graphics::consts;
+    use rules::Setup;
+    use super::*;
+
+    #[test]
+    fn greedy_outlasts_idle(){
+        let play = |kind| {
+            let mut sim = Simulation::new(11, Setup::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT));
+            let mut bot = Bot::new(kind, sim.seed());
+
+            while !sim.is_over() && sim.elapsed() < 600000{
+                if let Some(action) = bot.act(&sim){
+                    sim.apply_input(action);
+                }
+                sim.tick(50);
+            }
+            sim.elapsed()
+        };
+
+        assert!(play(BotKind::Greedy) > play(BotKind::Idle));
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+use bot::{BotKind, BOTS};
+use graphics::consts as settings;
+use rules::{Difficulty, Mode, DIFFICULTIES, MODES};
+
+pub const USAGE: &str = "Usage: ld39 [options]
+
+Options:
+    --seed <number>        Play the game with this seed
+    --width <cells>        Width of the field, scrolls when wider than the console
+    --height <cells>       Height of the field, scrolls when taller than the console
+    --difficulty <name>    easy, normal, hard, nightmare or custom from the config
+    --mode <name>          classic or zen
+    --replay <path>        Watch a recorded game
+    --record <path>        Where to record games to
+    --headless             Play without a screen and print the result, needs --bot or --replay
+    --bot <name>           Let a bot play: idle, wander or greedy
+    --config <path>        Use this config file
+    --help                 Show this message";
+
+///What the game was started with. Anything left out is `None` or its default.
+#[derive(Debug)]
+pub struct Options{
+    pub seed: Option<u64>,
+    pub width: Option<i16>,
+    pub height: Option<i16>,
+    pub difficulty: Difficulty,
+    pub mode: Mode,
+    pub replay: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub headless: bool,
+    pub bot: Option<BotKind>,
+    pub config: Option<PathBuf>,
+    pub help: bool
+}
+
+impl Options{
+    pub fn new() -> Options{
+        Options {
+            seed: None,
+            width: None,
+            height: None,
+            difficulty: Difficulty::Normal,
+            mode: Mode::Classic,
+            replay: None,
+            record: None,
+            headless: false,
+            bot: None,
+            config: None,
+            help: false
+        }
+    }
+}
+
+impl Default for Options{
+    fn default() -> Options{
+        Options::new()
+    }
+}
+
+///Reads the options from the arguments, without the program name. The error
+///says which argument was wrong and what was expected instead.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String>{
+    let mut options = Options::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next(){
+        match &arg[..]{
+            "--headless" => options.headless = true,
+            "--help" | "-h" => options.help = true,
+            "--seed" | "--width" | "--height" | "--difficulty" | "--mode" | "--replay" | "--record" | "--bot" | "--config" => {
+                let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+                set(&mut options, &arg, value)?;
+            },
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+
+    if options.headless && options.bot.is_none() && options.replay.is_none(){
+        return Err("--headless needs a --bot or a --replay to play the game".to_string());
+    }
+    if options.replay.is_some() && options.bot.is_some(){
+        return Err("--replay and --bot can not be used together".to_string());
+    }
+
+    Ok(options)
+}
+
+fn set(options: &mut Options, name: &str, value: String) -> Result<(), String>{
+    match name{
+        "--seed" => options.seed = Some(value.parse()
+            .map_err(|_| format!("--seed must be a whole number, got: {}", value))?),
+        "--width" => options.width = Some(size(name, &value, settings::MIN_FIELD_WIDTH, settings::MAX_FIELD_WIDTH)?),
+        "--height" => options.height = Some(size(name, &value, settings::MIN_FIELD_HEIGHT, settings::MAX_FIELD_HEIGHT)?),
+        "--difficulty" => options.difficulty = Difficulty::from_name(&value)
+            .ok_or_else(|| one_of(name, &value, &DIFFICULTIES.iter().map(|d| d.name()).collect::<Vec<_>>()))?,
+        "--mode" => options.mode = Mode::from_name(&value)
+            .ok_or_else(|| one_of(name, &value, &MODES.iter().map(|m| m.name()).collect::<Vec<_>>()))?,
+        "--bot" => options.bot = Some(BotKind::from_name(&value)
+            .ok_or_else(|| one_of(name, &value, &BOTS.iter().map(|b| b.name()).collect::<Vec<_>>()))?),
+        "--replay" => options.replay = Some(PathBuf::from(value)),
+        "--record" => options.record = Some(PathBuf::from(value)),
+        _ => options.config = Some(PathBuf::from(value)),
+    }
+
+    Ok(())
+}
+
+fn size(name: &str, value: &str, min: i16, max: i16) -> Result<i16, String>{
+    match value.parse::<i64>(){
+        Ok(size) if size >= min as i64 && size <= max as i64 => Ok(size as i16),
+        Ok(_) => Err(format!("{} must be from {} to {}, got: {}", name, min, max, value)),
+        Err(_) => Err(format!("{} must be a number, got: {}", name, value)),
+    }
+}
+
+fn one_of(name: &str, value: &str, names: &[&str]) -> String{
+    format!("{} must be one of {}, got: {}", name, names.join(", "), value)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn args(line: &str) -> Vec<String>{
+        line.split_whitespace().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_every_option(){
+        let options = parse(args("--seed 12 --width 200 --height 60 --difficulty hard --mode zen \
+            --record out.replay --headless --bot greedy --config my.txt")).unwrap();
+
+        assert_eq!(options.seed, Some(12));
+        assert_eq!((options.width, options.height), (Some(200), Some(60)));
+        assert_eq!(options.difficulty, Difficulty::Hard);
+        assert_eq!(options.mode, Mode::Zen);
+        assert_eq!(options.record, Some(PathBuf::from("out.replay")));
+        assert!(options.headless);
+        assert_eq!(options.bot, Some(BotKind::Greedy));
+        assert_eq!(options.config, Some(PathBuf::from("my.txt")));
+    }
+
+    #[test]
+    fn explains_bad_arguments(){
+        assert_eq!(parse(args("--width 10")).unwrap_err(), "--width must be from 80 to 2000, got: 10");
+        assert_eq!(parse(args("--height 40000")).unwrap_err(), "--height must be from 20 to 1000, got: 40000");
+        assert_eq!(parse(args("--difficulty silly")).unwrap_err(),
+            "--difficulty must be one of easy, normal, hard, nightmare, custom, got: silly");
+        assert_eq!(parse(args("--seed")).unwrap_err(), "--seed needs a value");
+        assert_eq!(parse(args("--seed -3")).unwrap_err(), "--seed must be a whole number, got: -3");
+        assert_eq!(parse(args("--seed 0")).unwrap().seed, Some(0));
+        assert!(parse(args("--headless")).is_err());
+        assert!(parse(args("--fly")).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use bindings::{Action, KeyMap, Preset, ACTIONS};
+use input::parse_code;
+use scheduler::Rates;
+use rules::{Difficulty, Rules, RULE_NAMES};
+use util::data_dir;
+
+///User settings, stored as `name = value` lines. Anything left out keeps
+///its default.
+pub struct Config{
+    pub keys: KeyMap,
+    pub rates: Rates,
+    ///What the custom difficulty plays like
+    pub rules: Rules
+}
+
+impl Config{
+    pub fn new() -> Config{
+        Config {keys: KeyMap::new(), rates: Rates::new(), rules: Rules::preset(Difficulty::Custom)}
+    }
+
+    pub fn default_path() -> PathBuf{
+        data_dir().join("config.txt")
+    }
+
+    ///Loads the config, a missing file just means the defaults
+    pub fn load(path: &Path) -> io::Result<Config>{
+        match File::open(path){
+            Ok(file) => Config::read(BufReader::new(file)),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()>{
+        if let Some(dir) = path.parent(){
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = File::create(path)?;
+
+        writeln!(file, "# Ash Dash settings")?;
+        writeln!(file, "# Simulation steps and screen updates per second")?;
+        writeln!(file, "tick_rate = {}", self.rates.ticks_per_second)?;
+        writeln!(file, "frame_rate = {}", self.rates.frames_per_second)?;
+        writeln!(file, "# The custom difficulty")?;
+        for name in RULE_NAMES.iter(){
+            writeln!(file, "rules.{} = {}", name, self.rules.get(name).unwrap_or(0))?;
+        }
+        writeln!(file, "# Keys are letters, digits, Up, Down, Left, Right, Enter, Escape, Space or Back")?;
+        for action in ACTIONS.iter(){
+            writeln!(file, "key.{} = {}", action.name(), self.keys.describe(*action))?;
+        }
+
+        Ok(())
+    }
+
+    fn read<R: BufRead>(reader: R) -> io::Result<Config>{
+        let mut config = Config::new();
+
+        for (number, line) in reader.lines().enumerate(){
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#'){
+                continue;
+            }
+
+            let (name, value) = match line.find('='){
+                Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+                None => return Err(invalid(number, "expected name = value")),
+            };
+
+            if name == "preset"{
+                let preset = Preset::from_name(value)
+                    .ok_or_else(|| invalid(number, "the preset must be arrows, wasd or hjkl"))?;
+                config.keys = KeyMap::preset(preset);
+            }else if name == "tick_rate"{
+                config.rates.ticks_per_second = rate(number, value)?;
+            }else if name == "frame_rate"{
+                config.rates.frames_per_second = rate(number, value)?;
+            }else if let Some(rule) = name.strip_prefix("rules."){
+                let value = value.parse().map_err(|_| invalid(number, "rules must be whole numbers"))?;
+                config.rules.set(rule, value).map_err(|err| invalid(number, &err))?;
+            }else if let Some(action) = name.strip_prefix("key."){
+                let action = Action::from_name(action)
+                    .ok_or_else(|| invalid(number, &format!("unknown action {}", action)))?;
+                let codes = value.split_whitespace()
+                    .map(|key| parse_code(key).ok_or_else(|| invalid(number, &format!("unknown key {}", key))))
+                    .collect::<io::Result<Vec<u32>>>()?;
+                config.keys.bind(action, codes);
+            }else{
+                return Err(invalid(number, &format!("unknown setting {}", name)));
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+impl Default for Config{
+    fn default() -> Config{
+        Config::new()
+    }
+}
+
+///A positive number of times per second, at most once a millisecond
+fn rate(line: usize, value: &str) -> io::Result<u32>{
+    match value.parse(){
+        Ok(rate) if rate > 0 && rate <= 1000 => Ok(rate),
+        _ => Err(invalid(line, "rates must be a number from 1 to 1000")),
+    }
+}
+
+fn invalid(line: usize, message: &str) -> io::Error{
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line + 1, message))
+}
+
+#[cfg(test)]
+mod tests{
+    use bindings::{Action, KeyMap, Preset};
+    use super::*;
+
+    #[test]
+    fn missing_settings_keep_their_defaults(){
+        let config = Config::read(&b"# nothing\n"[..]).unwrap();
+        assert!(config.keys == KeyMap::new());
+    }
+
+    #[test]
+    fn preset_then_overrides(){
+        let config = Config::read(&b"preset = hjkl\nkey.Pause = Space\n"[..]).unwrap();
+        let mut expected = KeyMap::preset(Preset::Hjkl);
+        expected.bind(Action::Pause, vec![0x20]);
+
+        assert!(config.keys == expected);
+    }
+
+    #[test]
+    fn reads_rates(){
+        let config = Config::read(&b"tick_rate = 50\n"[..]).unwrap();
+        assert_eq!(config.rates.ticks_per_second, 50);
+        assert_eq!(config.rates.frames_per_second, Rates::new().frames_per_second);
+        assert!(Config::read(&b"frame_rate = 0\n"[..]).is_err());
+    }
+
+    #[test]
+    fn reads_custom_rules(){
+        let config = Config::read(&b"rules.drain = 4\n"[..]).unwrap();
+        assert_eq!(config.rules.drain, 4);
+        assert!(Config::read(&b"rules.drain = -1\n"[..]).is_err());
+        assert!(Config::read(&b"rules.lava = 1\n"[..]).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_keys(){
+        assert!(Config::read(&b"key.MoveUp = Banana\n"[..]).is_err());
+        assert!(Config::read(&b"key.Fly = W\n"[..]).is_err());
+    }
+}
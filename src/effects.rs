@@ -0,0 +1,184 @@
+use std::cmp;
+
+///Something that stays on the player for a while
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Effect{
+    ///Takes a magma hit in place of the battery, once per stack
+    Shield,
+    ///No ash falls
+    Frozen,
+    ///Pulls the ₽ around the player closer every second
+    Magnet,
+    ///Magma got into the battery, so it drains faster
+    Scorched
+}
+
+pub const EFFECTS: [Effect; 4] = [Effect::Shield, Effect::Frozen, Effect::Magnet, Effect::Scorched];
+
+///What happens when the player gets an effect they already have
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Stacking{
+    ///The time starts over
+    Refresh,
+    ///The time is added on
+    Extend,
+    ///Another stack, up to this many, and the time starts over
+    Stack(u16)
+}
+
+impl Effect{
+    ///The name used in save files
+    pub fn name(&self) -> &'static str{
+        match *self{
+            Effect::Shield => "shield",
+            Effect::Frozen => "frozen",
+            Effect::Magnet => "magnet",
+            Effect::Scorched => "scorched",
+        }
+    }
+
+    pub fn label(&self) -> &'static str{
+        match *self{
+            Effect::Shield => "Shield",
+            Effect::Frozen => "Frozen",
+            Effect::Magnet => "Magnet",
+            Effect::Scorched => "Scorched",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Effect>{
+        EFFECTS.iter().find(|effect| effect.name() == name).cloned()
+    }
+
+    ///How long the effect lasts, in milliseconds
+    pub fn millis(&self) -> u64{
+        match *self{
+            Effect::Shield => 30000,
+            Effect::Frozen => 10000,
+            Effect::Magnet => 8000,
+            Effect::Scorched => 5000,
+        }
+    }
+
+    pub fn stacking(&self) -> Stacking{
+        match *self{
+            Effect::Shield => Stacking::Stack(3),
+            Effect::Frozen | Effect::Magnet => Stacking::Refresh,
+            Effect::Scorched => Stacking::Extend,
+        }
+    }
+}
+
+///An effect the player has right now
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Status{
+    pub effect: Effect,
+    ///Milliseconds until it wears off
+    pub left: u64,
+    pub stacks: u16
+}
+
+///The effects on the player, in the order they were picked up
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Effects{
+    active: Vec<Status>
+}
+
+impl Effects{
+    pub fn new() -> Effects{
+        Effects {active: Vec::new()}
+    }
+
+    pub fn from_active(active: Vec<Status>) -> Effects{
+        Effects {active: active}
+    }
+
+    pub fn active(&self) -> &[Status]{
+        &self.active
+    }
+
+    ///Gives the player the effect, following its stacking rule if they already have it
+    pub fn add(&mut self, effect: Effect){
+        match self.active.iter_mut().find(|status| status.effect == effect){
+            Some(status) => match effect.stacking(){
+                Stacking::Refresh => status.left = effect.millis(),
+                Stacking::Extend => status.left += effect.millis(),
+                Stacking::Stack(most) => {
+                    status.stacks = cmp::min(status.stacks + 1, most);
+                    status.left = effect.millis();
+                },
+            },
+            None => self.active.push(Status {effect: effect, left: effect.millis(), stacks: 1}),
+        }
+    }
+
+    pub fn has(&self, effect: Effect) -> bool{
+        self.stacks(effect) > 0
+    }
+
+    pub fn stacks(&self, effect: Effect) -> u16{
+        self.active.iter().find(|status| status.effect == effect).map_or(0, |status| status.stacks)
+    }
+
+    ///Uses up a stack of the effect, returns false if the player did not have it
+    pub fn consume(&mut self, effect: Effect) -> bool{
+        match self.active.iter().position(|status| status.effect == effect){
+            Some(i) => {
+                self.active[i].stacks -= 1;
+                if self.active[i].stacks == 0{
+                    self.active.remove(i);
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    ///Counts the effects down by `dt` milliseconds and gives back the ones
+    ///that wore off
+    pub fn tick(&mut self, dt: u64) -> Vec<Effect>{
+        let mut ended = Vec::new();
+
+        for status in &mut self.active{
+            status.left = status.left.saturating_sub(dt);
+            if status.left == 0{
+                ended.push(status.effect);
+            }
+        }
+        self.active.retain(|status| status.left > 0);
+
+        ended
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn follows_the_stacking_rules(){
+        let mut effects = Effects::new();
+        for _ in 0..5{
+            effects.add(Effect::Shield);
+            effects.add(Effect::Scorched);
+            effects.add(Effect::Frozen);
+        }
+
+        assert_eq!(effects.stacks(Effect::Shield), 3);
+        assert_eq!(effects.stacks(Effect::Scorched), 1);
+        assert_eq!(effects.active()[1].left, 5 * Effect::Scorched.millis());
+        assert_eq!(effects.active()[2].left, Effect::Frozen.millis());
+    }
+
+    #[test]
+    fn wears_off(){
+        let mut effects = Effects::new();
+        effects.add(Effect::Magnet);
+        effects.add(Effect::Shield);
+
+        assert_eq!(effects.tick(Effect::Magnet.millis()), vec![Effect::Magnet]);
+        assert!(effects.consume(Effect::Shield));
+        assert!(!effects.consume(Effect::Shield));
+        assert!(effects.active().is_empty());
+    }
+}
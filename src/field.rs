@@ -0,0 +1,214 @@
+use super::Powerup;
+
+///Which cells something can land on
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Ground{
+    ///Cells with nothing on them
+    Empty,
+    ///Every cell that is not ash yet, powerups included
+    NotAsh
+}
+
+///The grid of powerups and ash the player runs around on. Its size is picked
+///when the game starts, so it lives on the heap. Keeps count of its empty
+///and ash cells, so a random free cell can be found without searching.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Field{
+    width: i16,
+    height: i16,
+    cells: Vec<Option<Powerup>>,
+    ///How long what is on each cell has been there, counted by whoever cares
+    ages: Vec<u8>,
+    empty: Tally,
+    ash: Tally
+}
+
+impl Field{
+    pub fn new(width: i16, height: i16) -> Field{
+        let size = width as usize * height as usize;
+
+        Field {
+            width: width,
+            height: height,
+            cells: vec![None; size],
+            ages: vec![0; size],
+            empty: Tally::new(size, true),
+            ash: Tally::new(size, false)
+        }
+    }
+
+    pub fn width(&self) -> i16{
+        self.width
+    }
+
+    pub fn height(&self) -> i16{
+        self.height
+    }
+
+    pub fn contains(&self, x: i16, y: i16) -> bool{
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+
+    ///Anything outside the field is empty
+    pub fn get(&self, x: i16, y: i16) -> Option<Powerup>{
+        if self.contains(x, y){
+            self.cells[self.index(x, y)]
+        }else{
+            None
+        }
+    }
+
+    ///Writes outside the field are ignored. A cell that changes starts over at age 0.
+    pub fn set(&mut self, x: i16, y: i16, powerup: Option<Powerup>){
+        if self.contains(x, y){
+            let index = self.index(x, y);
+            let old = self.cells[index];
+
+            if old.is_none() != powerup.is_none(){
+                self.empty.add(index, powerup.is_none());
+            }
+            if (old == Some(Powerup::Destroyed)) != (powerup == Some(Powerup::Destroyed)){
+                self.ash.add(index, powerup == Some(Powerup::Destroyed));
+            }
+            if old != powerup{
+                self.ages[index] = 0;
+            }
+            self.cells[index] = powerup;
+        }
+    }
+
+    pub fn age(&self, x: i16, y: i16) -> u8{
+        if self.contains(x, y){
+            self.ages[self.index(x, y)]
+        }else{
+            0
+        }
+    }
+
+    pub fn set_age(&mut self, x: i16, y: i16, age: u8){
+        if self.contains(x, y){
+            let index = self.index(x, y);
+            self.ages[index] = age;
+        }
+    }
+
+    ///Whether the cell `x, y` is in the field and on this ground
+    pub fn is_on(&self, ground: Ground, x: i16, y: i16) -> bool{
+        self.contains(x, y) && match (ground, self.get(x, y)){
+            (Ground::Empty, powerup) => powerup.is_none(),
+            (Ground::NotAsh, powerup) => powerup != Some(Powerup::Destroyed),
+        }
+    }
+
+    ///How many cells are on this ground
+    pub fn count(&self, ground: Ground) -> usize{
+        match ground{
+            Ground::Empty => self.empty.total,
+            Ground::NotAsh => self.cells.len() - self.ash.total,
+        }
+    }
+
+    ///The `n`th cell on this ground, going down each column from the left.
+    ///`n` has to be less than the `count`.
+    pub fn nth(&self, ground: Ground, n: usize) -> (i16, i16){
+        let index = match ground{
+            Ground::Empty => self.empty.nth(n, false),
+            Ground::NotAsh => self.ash.nth(n, true),
+        };
+
+        ((index / self.height as usize) as i16, (index % self.height as usize) as i16)
+    }
+
+    fn index(&self, x: i16, y: i16) -> usize{
+        x as usize * self.height as usize + y as usize
+    }
+}
+
+///Which cells are marked, as a Fenwick tree so marking a cell and finding
+///the `n`th marked one both take log time
+#[derive(Clone, PartialEq, Debug)]
+struct Tally{
+    ///1 based, each node counts the marked cells in the `lowest bit` cells up to it
+    tree: Vec<usize>,
+    total: usize
+}
+
+impl Tally{
+    fn new(size: usize, marked: bool) -> Tally{
+        let tree = (0..size + 1)
+            .map(|node| if marked { node & node.wrapping_neg() }else{ 0 })
+            .collect();
+
+        Tally {tree: tree, total: if marked { size }else{ 0 }}
+    }
+
+    fn add(&mut self, index: usize, marked: bool){
+        let mut node = index + 1;
+        while node < self.tree.len(){
+            if marked { self.tree[node] += 1 }else{ self.tree[node] -= 1 }
+            node += node & node.wrapping_neg();
+        }
+
+        if marked { self.total += 1 }else{ self.total -= 1 }
+    }
+
+    ///The index of the `n`th marked cell, or of the `n`th unmarked one when `unmarked`
+    fn nth(&self, n: usize, unmarked: bool) -> usize{
+        let size = self.tree.len() - 1;
+        let mut step = if size == 0 { 0 }else{ 1 << (63 - (size as u64).leading_zeros()) };
+        let mut node = 0;
+        let mut left = n;
+
+        while step > 0{
+            let next = node + step;
+            if next <= size{
+                let count = if unmarked { step - self.tree[next] }else{ self.tree[next] };
+                if count <= left{
+                    node = next;
+                    left -= count;
+                }
+            }
+            step >>= 1;
+        }
+
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn out_of_bounds_is_empty(){
+        let mut field = Field::new(4, 3);
+        field.set(4, 0, Some(Powerup::Charge));
+        field.set(3, 2, Some(Powerup::Reset));
+
+        assert_eq!(field.get(4, 0), None);
+        assert_eq!(field.get(-1, 0), None);
+        assert_eq!(field.get(3, 2), Some(Powerup::Reset));
+    }
+
+    #[test]
+    fn finds_the_nth_free_cell(){
+        let mut field = Field::new(3, 3);
+        field.set(0, 0, Some(Powerup::Destroyed));
+        field.set(0, 2, Some(Powerup::Charge));
+        field.set(1, 0, Some(Powerup::Destroyed));
+
+        assert_eq!(field.count(Ground::Empty), 6);
+        assert_eq!(field.nth(Ground::Empty, 0), (0, 1));
+        assert_eq!(field.nth(Ground::Empty, 1), (1, 1));
+        assert_eq!(field.count(Ground::NotAsh), 7);
+        assert_eq!(field.nth(Ground::NotAsh, 1), (0, 2));
+
+        assert!(field.is_on(Ground::NotAsh, 0, 2));
+        assert!(!field.is_on(Ground::Empty, 0, 2));
+        assert!(!field.is_on(Ground::NotAsh, 3, 0));
+
+        field.set(0, 0, None);
+        assert_eq!(field.nth(Ground::Empty, 0), (0, 0));
+        assert_eq!(field.count(Ground::NotAsh), 8);
+    }
+}
@@ -0,0 +1,111 @@
+use std::cmp;
+
+///How close the player may get to the edge of the view before it scrolls
+const DEAD_ZONE_X: i16 = 12;
+const DEAD_ZONE_Y: i16 = 4;
+
+///The part of the field that is on screen. It stays put while the player
+///walks around the middle and only scrolls once they get within the dead
+///zone of an edge, or never when the whole field fits.
+pub struct Camera{
+    x: i16,
+    y: i16,
+    width: i16,
+    height: i16,
+    world_width: i16,
+    world_height: i16
+}
+
+impl Camera{
+    pub fn new(width: i16, height: i16) -> Camera{
+        Camera {x: 0, y: 0, width: width, height: height, world_width: width, world_height: height}
+    }
+
+    ///Shows as much of a `world_width` by `world_height` field as fits in the view,
+    ///starting from its top left corner
+    pub fn resize(&mut self, width: i16, height: i16, world_width: i16, world_height: i16){
+        self.width = cmp::min(width, world_width);
+        self.height = cmp::min(height, world_height);
+        self.world_width = world_width;
+        self.world_height = world_height;
+        self.x = 0;
+        self.y = 0;
+    }
+
+    pub fn width(&self) -> i16{
+        self.width
+    }
+
+    pub fn height(&self) -> i16{
+        self.height
+    }
+
+    ///The field cell in the top left corner of the view
+    pub fn origin(&self) -> (i16, i16){
+        (self.x, self.y)
+    }
+
+    ///Scrolls until the field cell `x, y` is out of the dead zone, returns
+    ///whether the view moved
+    pub fn follow(&mut self, x: i16, y: i16) -> bool{
+        let new_x = follow_axis(self.x, x, self.width, self.world_width, DEAD_ZONE_X);
+        let new_y = follow_axis(self.y, y, self.height, self.world_height, DEAD_ZONE_Y);
+        let moved = (new_x, new_y) != (self.x, self.y);
+
+        self.x = new_x;
+        self.y = new_y;
+        moved
+    }
+
+    ///Where the field cell `x, y` is inside the view, if it is on screen at all
+    pub fn to_view(&self, x: i16, y: i16) -> Option<(i16, i16)>{
+        let (view_x, view_y) = (x - self.x, y - self.y);
+
+        if view_x >= 0 && view_y >= 0 && view_x < self.width && view_y < self.height{
+            Some((view_x, view_y))
+        }else{
+            None
+        }
+    }
+}
+
+fn follow_axis(start: i16, target: i16, size: i16, world_size: i16, dead_zone: i16) -> i16{
+    //Small views get a smaller dead zone so there is still room to walk
+    let dead_zone = cmp::min(dead_zone, (size - 1) / 3);
+    let mut start = start;
+
+    if target < start + dead_zone{
+        start = target - dead_zone;
+    }else if target > start + size - 1 - dead_zone{
+        start = target - size + 1 + dead_zone;
+    }
+
+    cmp::max(0, cmp::min(start, world_size - size))
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn scrolls_only_past_the_dead_zone(){
+        let mut camera = Camera::new(0, 0);
+        camera.resize(40, 10, 100, 30);
+
+        assert!(!camera.follow(27, 5));
+        assert!(camera.follow(28, 5));
+        assert_eq!(camera.origin(), (1, 0));
+        assert_eq!(camera.to_view(28, 5), Some((27, 5)));
+        assert_eq!(camera.to_view(0, 5), None);
+    }
+
+    #[test]
+    fn stops_at_the_edge_of_the_field(){
+        let mut camera = Camera::new(0, 0);
+        camera.resize(40, 10, 100, 30);
+        camera.follow(99, 29);
+
+        assert_eq!(camera.origin(), (60, 20));
+        assert_eq!(camera.to_view(99, 29), Some((39, 9)));
+    }
+}
@@ -3,4 +3,19 @@ pub const STATUS_BAR_HEIGHT: i16 = 5;
 pub const MENU_HEIGHT: i16 = 5;
 pub const WORLD_WIDTH: i16 = 140;
 pub const WORLD_HEIGHT: i16 = 40;
-pub const TOTAL_HEIGHT: i16 = WORLD_HEIGHT + MENU_HEIGHT + STATUS_BAR_HEIGHT;
+///The world already counts the menu
+pub const TOTAL_HEIGHT: i16 = WORLD_HEIGHT + STATUS_BAR_HEIGHT;
+///First screen row of the field, just below the menu
+pub const FIELD_TOP: i16 = MENU_HEIGHT + 1;
+///The field used when neither the command line nor the console says otherwise
+pub const FIELD_WIDTH: i16 = WORLD_WIDTH;
+pub const FIELD_HEIGHT: i16 = WORLD_HEIGHT - FIELD_TOP;
+///Anything smaller and the menus stop fitting. Smaller consoles scroll.
+pub const MIN_FIELD_WIDTH: i16 = 80;
+pub const MIN_FIELD_HEIGHT: i16 = 20;
+///Anything bigger takes too long to scan every tick
+pub const MAX_FIELD_WIDTH: i16 = 2000;
+pub const MAX_FIELD_HEIGHT: i16 = 1000;
+///The least of the field shown, however small the console
+pub const MIN_VIEW_WIDTH: i16 = 40;
+pub const MIN_VIEW_HEIGHT: i16 = 8;
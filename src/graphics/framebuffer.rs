@@ -0,0 +1,213 @@
+use cannon::color;
+use super::consts;
+use super::renderer::Renderer;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cell{
+    pub character: char,
+    pub foreground: u16,
+    pub background: u16
+}
+
+///A renderer that draws into memory instead of a console. Writes outside
+///the buffer are dropped.
+#[derive(Clone)]
+pub struct Framebuffer{
+    width: i16,
+    height: i16,
+    cells: Vec<Cell>,
+    cursor_x: i16,
+    cursor_y: i16,
+    foreground: u16,
+    background: u16
+}
+
+impl Framebuffer{
+    ///Creates a buffer the size of the whole game screen
+    pub fn new() -> Framebuffer{
+        Framebuffer::with_size(consts::WORLD_WIDTH, consts::TOTAL_HEIGHT)
+    }
+
+    pub fn with_size(width: i16, height: i16) -> Framebuffer{
+        let blank = Cell {character: ' ', foreground: color::BLACK, background: color::BLACK};
+
+        Framebuffer {
+            width: width,
+            height: height,
+            cells: vec![blank; (width as usize) * (height as usize)],
+            cursor_x: 0,
+            cursor_y: 0,
+            foreground: color::BLACK,
+            background: color::BLACK
+        }
+    }
+
+    pub fn width(&self) -> i16{
+        self.width
+    }
+
+    pub fn height(&self) -> i16{
+        self.height
+    }
+
+    pub fn cell(&self, x: i16, y: i16) -> Option<&Cell>{
+        self.index(x, y).map(|i| &self.cells[i])
+    }
+
+    ///Dumps the characters, then the background and foreground colors as one
+    ///hex digit per cell
+    pub fn snapshot(&self) -> String{
+        let mut text = String::new();
+        let mut background = String::new();
+        let mut foreground = String::new();
+
+        for row in self.cells.chunks(self.width as usize){
+            for cell in row{
+                text.push(cell.character);
+                background.push(palette_digit(cell.background));
+                foreground.push(palette_digit(cell.foreground));
+            }
+            text.push('\n');
+            background.push('\n');
+            foreground.push('\n');
+        }
+
+        format!("{}\n{}\n{}", text, background, foreground)
+    }
+
+    ///Draws the cells that differ from `previous` onto `console`, or clears it
+    ///and draws them all without one, then flushes it. Runs of changed cells
+    ///in the same colors are written together.
+    pub fn present<R: Renderer>(&self, previous: Option<&Framebuffer>, console: &mut R){
+        let previous = previous.filter(|p| (p.width, p.height) == (self.width, self.height));
+        let changed = |i: usize| previous.is_none_or(|p| p.cells[i] != self.cells[i]);
+        let mut colors = None;
+
+        if previous.is_none(){
+            console.set_color(color::BLACK, color::BLACK);
+            console.clear_screen();
+        }
+
+        for y in 0..self.height{
+            let mut x = 0;
+
+            while x < self.width{
+                let start = self.index(x, y).unwrap();
+                if !changed(start){
+                    x += 1;
+                    continue;
+                }
+
+                let run_colors = (self.cells[start].foreground, self.cells[start].background);
+                let mut run = String::new();
+                let run_x = x;
+
+                while x < self.width{
+                    let i = self.index(x, y).unwrap();
+                    if !changed(i) || (self.cells[i].foreground, self.cells[i].background) != run_colors{
+                        break;
+                    }
+
+                    run.push(self.cells[i].character);
+                    x += 1;
+                }
+
+                if colors != Some(run_colors){
+                    console.set_color(run_colors.0, run_colors.1);
+                    colors = Some(run_colors);
+                }
+                console.set_cursor_position(run_x, y);
+                console.write(&run);
+            }
+        }
+
+        console.flush();
+    }
+
+    fn index(&self, x: i16, y: i16) -> Option<usize>{
+        if x < 0 || y < 0 || x >= self.width || y >= self.height{
+            None
+        }else{
+            Some((y as usize) * (self.width as usize) + (x as usize))
+        }
+    }
+
+    fn put(&mut self, x: i16, y: i16, character: char){
+        let cell = Cell {character: character, foreground: self.foreground, background: self.background};
+
+        if let Some(i) = self.index(x, y){
+            self.cells[i] = cell;
+        }
+    }
+}
+
+impl Default for Framebuffer{
+    fn default() -> Framebuffer{
+        Framebuffer::new()
+    }
+}
+
+impl Renderer for Framebuffer{
+    fn set_color(&mut self, foreground: u16, background: u16){
+        self.foreground = foreground;
+        self.background = background;
+    }
+
+    fn set_cursor_position(&mut self, x: i16, y: i16){
+        self.cursor_x = x;
+        self.cursor_y = y;
+    }
+
+    fn write(&mut self, string: &str){
+        for character in string.chars(){
+            if character == '\n'{
+                self.cursor_x = 0;
+                self.cursor_y += 1;
+                continue;
+            }
+
+            let (x, y) = (self.cursor_x, self.cursor_y);
+            self.put(x, y, character);
+            self.cursor_x += 1;
+
+            if self.cursor_x >= self.width{
+                self.cursor_x = 0;
+                self.cursor_y += 1;
+            }
+        }
+    }
+
+    fn write_character(&mut self, x: i16, y: i16, character: u8){
+        self.put(x, y, character as char);
+    }
+
+    fn clear_screen(&mut self){
+        for i in 0..self.cells.len(){
+            self.cells[i] = Cell {character: ' ', foreground: self.foreground, background: self.background};
+        }
+        self.cursor_x = 0;
+        self.cursor_y = 0;
+    }
+}
+
+///The windows console palette index of a color as a hex digit
+fn palette_digit(c: u16) -> char{
+    match c{
+        color::BLACK => '0',
+        color::DARK_BLUE => '1',
+        color::DARK_GREEN => '2',
+        color::DARK_CYAN => '3',
+        color::DARK_RED => '4',
+        color::DARK_MAGENTA => '5',
+        color::BROWN => '6',
+        color::LIGHT_GRAY => '7',
+        color::DARK_GRAY => '8',
+        color::BLUE => '9',
+        color::GREEN => 'a',
+        color::CYAN => 'b',
+        color::RED => 'c',
+        color::MAGENTA => 'd',
+        color::YELLOW => 'e',
+        _ => 'f',
+    }
+}
@@ -0,0 +1,117 @@
+use cannon::color;
+use super::renderer::Renderer;
+use util::Position;
+use field::Field;
+use Powerup;
+
+///Size of the minimap in the top menu, in characters
+pub const MINIMAP_WIDTH: i16 = 20;
+pub const MINIMAP_HEIGHT: i16 = 3;
+
+///What a block of field cells behind one minimap character holds
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Block{
+    pub cells: u32,
+    pub ash: u32,
+    pub powerups: u32
+}
+
+///Splits the field into `columns` by `rows` blocks, counting the ash and
+///powerups in each. Blocks are `[column][row]` like the field.
+pub fn downsample(field: &Field, columns: i16, rows: i16) -> Vec<Vec<Block>>{
+    let mut blocks = vec![vec![Block::default(); rows as usize]; columns as usize];
+
+    for x in 0..field.width(){
+        for y in 0..field.height(){
+            let (column, row) = block_of(field, x, y, columns, rows);
+            let block = &mut blocks[column as usize][row as usize];
+            block.cells += 1;
+
+            match field.get(x, y){
+                Some(Powerup::Destroyed) => block.ash += 1,
+                Some(Powerup::Rock) | None => (),
+                Some(_) => block.powerups += 1,
+            }
+        }
+    }
+
+    blocks
+}
+
+///Draws the minimap with its top left corner at `x, y`
+pub fn draw_minimap<R: Renderer>(console: &mut R, x: i16, y: i16, field: &Field, player: &Position){
+    let blocks = downsample(field, MINIMAP_WIDTH, MINIMAP_HEIGHT);
+    let player = block_of(field, player.x, player.y - 1, MINIMAP_WIDTH, MINIMAP_HEIGHT);
+
+    for (column, blocks) in blocks.iter().enumerate(){
+        for (row, block) in blocks.iter().enumerate(){
+            let (column, row) = (column as i16, row as i16);
+
+            let (foreground, character) = if (column, row) == player{
+                (color::BLUE, '@')
+            }else{
+                match block.powerups{
+                    0 => (color::BLACK, ' '),
+                    1..=9 => (color::YELLOW, (b'0' + block.powerups as u8) as char),
+                    _ => (color::YELLOW, '*'),
+                }
+            };
+
+            console.set_color(foreground, ash_shade(block));
+            console.set_cursor_position(x + column, y + row);
+            console.write(&character.to_string());
+        }
+    }
+}
+
+///Browner for clear ground, darker the more of the block is ash
+fn ash_shade(block: &Block) -> u16{
+    if block.ash == 0 || block.cells == 0{
+        return color::BROWN;
+    }
+
+    match block.ash * 100 / block.cells{
+        0..=19 => color::RED,
+        20..=49 => color::DARK_RED,
+        _ => color::BLACK,
+    }
+}
+
+fn block_of(field: &Field, x: i16, y: i16, columns: i16, rows: i16) -> (i16, i16){
+    let column = x as i32 * columns as i32 / field.width() as i32;
+    let row = y as i32 * rows as i32 / field.height() as i32;
+    (column as i16, row as i16)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn counts_ash_and_powerups_per_block(){
+        let mut field = Field::new(40, 6);
+        field.set(0, 0, Some(Powerup::Destroyed));
+        field.set(1, 1, Some(Powerup::Destroyed));
+        field.set(39, 5, Some(Powerup::Charge));
+        field.set(38, 4, Some(Powerup::Reset));
+
+        let blocks = downsample(&field, 20, 3);
+        assert_eq!(blocks[0][0], Block {cells: 4, ash: 2, powerups: 0});
+        assert_eq!(blocks[19][2], Block {cells: 4, ash: 0, powerups: 2});
+        assert_eq!(blocks[10][1], Block {cells: 4, ash: 0, powerups: 0});
+    }
+
+    #[test]
+    fn big_fields_do_not_overflow(){
+        let mut field = Field::new(400, 100);
+        for x in 0..400{
+            for y in 0..100{
+                field.set(x, y, Some(Powerup::Destroyed));
+            }
+        }
+
+        let blocks = downsample(&field, 20, 3);
+        assert!(blocks[0][0].ash > 656);
+        assert_eq!(ash_shade(&blocks[0][0]), color::BLACK);
+    }
+}
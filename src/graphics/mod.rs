@@ -1,219 +1,592 @@
 extern crate cannon;
 
 mod util;
+mod camera;
+mod minimap;
 pub mod consts;
+pub mod renderer;
+pub mod framebuffer;
+#[cfg(test)]
+mod tests;
 
-use cannon::*;
+use std::cmp;
+use cannon::color;
 use self::util::rect;
-use super::{Powerup, Field};
+use self::renderer::Renderer;
+use self::camera::Camera;
+use self::minimap::MINIMAP_WIDTH;
+use self::framebuffer::Framebuffer;
+use super::powerups::{self, KINDS};
+use super::field::Field;
+use super::highscore::HighScore;
+use super::rules::Difficulty;
+use super::volcano::{Phase, Volcano};
+use super::smoke::{Smoke, CLEAR_DENSITY};
+use super::simulation::Falling;
+use super::effects::{Effects, Status};
+use super::battery::Battery;
+use super::bindings::{Action, KeyMap, ACTIONS};
 use super::util::Position;
 
-pub struct Graphics{
-    console: Console,
-    status: String
+///How much room each effect gets in the top menu
+const EFFECT_WIDTH: i16 = 16;
+
+///Everything is drawn into `frame` first, `flush` then sends the console
+///only the cells that changed since the last one
+pub struct Graphics<R: Renderer>{
+    console: R,
+    frame: Framebuffer,
+    ///What the console shows, none when it has to be repainted from scratch
+    screen: Option<Framebuffer>,
+    ///The view of the field is as wide as the screen, `height` is where the status bar starts
+    width: i16,
+    height: i16,
+    camera: Camera,
+    ///The most of the field the console has room for
+    max_view: (i16, i16),
+    status: String,
+    ///Used for the key hints on screen
+    keys: KeyMap,
+    ///Drawn on top of the powerups, if there is one
+    volcano: Option<Volcano>,
+    ///Shadows of the ash about to land, also drawn on top of the powerups
+    falling: Vec<Falling>,
+    ///Shades the ground under everything on the field, if there is any
+    smoke: Option<Smoke>,
+    ///Shown on the main menu, like a save that could not be loaded
+    notice: String
 }
 
-impl Graphics{
-    pub fn new() -> Graphics{
-        let mut console = Console::new();
-        console.set_should_cls(true);
+impl<R: Renderer> Graphics<R>{
+    pub fn new(console: R) -> Graphics<R>{
+        Graphics {
+            console: console,
+            frame: Framebuffer::new(),
+            screen: None,
+            width: consts::WORLD_WIDTH,
+            height: consts::WORLD_HEIGHT,
+            camera: Camera::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT),
+            max_view: (consts::FIELD_WIDTH, consts::FIELD_HEIGHT),
+            status: "Welcome to LD 39".to_string(),
+            keys: KeyMap::new(),
+            volcano: None,
+            falling: Vec::new(),
+            smoke: None,
+            notice: String::new()
+        }
+    }
+
+    ///Lays the screen out around a field of the given size, scrolling it
+    ///when it is bigger than the view
+    pub fn set_field_size(&mut self, width: i16, height: i16){
+        self.camera.resize(self.max_view.0, self.max_view.1, width, height);
+        self.width = self.camera.width();
+        self.height = self.camera.height() + consts::FIELD_TOP;
+
+        if (self.frame.width(), self.frame.height()) != (self.width, self.total_height()){
+            self.frame = Framebuffer::with_size(self.width, self.total_height());
+            self.screen = None;
+        }
+    }
 
-        Graphics {console: console, status: "Welcome to LD 39".to_string()}
+    ///Sends everything drawn since the last flush to the console in one go
+    pub fn flush(&mut self){
+        self.frame.present(self.screen.as_ref(), &mut self.console);
+        self.screen = Some(self.frame.clone());
     }
 
-    pub fn draw_main_menu(&mut self){
-        draw_main_menu_top(&mut self.console);
-        draw_main_menu_center(&mut self.console);
-        draw_main_menu_info(&mut self.console);
+    ///How many field cells the console has room for. Takes effect on the
+    ///next `set_field_size`.
+    pub fn set_view_size(&mut self, width: i16, height: i16){
+        self.max_view = (width, height);
     }
 
+    ///Rows the screen takes up, `height` already counts the menu
+    pub fn total_height(&self) -> i16{
+        self.height + consts::STATUS_BAR_HEIGHT
+    }
+
+    pub fn set_keys(&mut self, keys: &KeyMap){
+        self.keys = keys.clone();
+    }
+
+    pub fn draw_main_menu(&mut self, can_continue: bool, difficulty: Difficulty){
+        let total_height = self.total_height();
+        draw_main_menu_top(&mut self.frame, self.width, total_height);
+        draw_main_menu_center(&mut self.frame, self.width, total_height, &self.keys);
+        draw_centered_string(&mut self.frame, self.width, &format!("Difficulty: < {} >  (Change With {}/{})",
+            difficulty.label(), self.keys.key_name(Action::MoveLeft), self.keys.key_name(Action::MoveRight)),
+            total_height - 12);
+        if !self.notice.is_empty(){
+            self.frame.set_color(color::RED, color::LIGHT_GRAY);
+            draw_centered_string(&mut self.frame, self.width, &self.notice, total_height - 11);
+        }
+        if can_continue{
+            self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+            draw_centered_string(&mut self.frame, self.width, &format!("To Continue Your Last Game Press {}",
+                self.keys.key_name(Action::Continue)), total_height - 4);
+        }
+        draw_main_menu_info(&mut self.frame, self.width, total_height);
+    }
+
+    ///Also repaints the whole console on the next flush, in case it was resized
     pub fn redraw_background(&mut self){
-        self.console.set_color(color::BLACK, color::BLACK);
-        self.console.clear_screen();
-
-        draw_field(&mut self.console);
-        draw_status_bar(&mut self.console, &self.status);
-        draw_menu(&mut self.console);
-    }
-
-    pub fn draw_end_game(&mut self, time: u64){
-        let start_x = (consts::WORLD_WIDTH / 2) - 7;
-        let start_y = (consts::WORLD_HEIGHT / 2) - 3;
-        self.console.set_color(color::BLACK, color::BLUE);
-        rect(&mut self.console, start_x - 1, start_y -1, start_x + 14, start_y + 6);
-        self.console.set_color(color::BLACK, color::LIGHT_GRAY);
-        rect(&mut self.console, start_x, start_y, start_x + 13, start_y + 5);
-        self.console.set_cursor_position(start_x + 2, start_y + 1);
-        self.console.write("Game Over!");
-        self.console.set_cursor_position(start_x + 2, start_y + 2);
-        self.console.write(&format!("Time: {}", time / 1000));
-        self.console.set_cursor_position(start_x + 2, start_y + 3);
-        self.console.write("R = Restart");
-        self.console.set_cursor_position(start_x + 2, start_y + 4);
-        self.console.write("Q = Quit");
+        self.frame.set_color(color::BLACK, color::BLACK);
+        self.frame.clear_screen();
+        self.screen = None;
+
+        draw_field(&mut self.frame, self.width, self.height);
+        draw_status_bar(&mut self.frame, self.width, self.height, &self.status);
+        draw_menu(&mut self.frame, self.width);
+    }
+
+    pub fn draw_end_game(&mut self, time: u64, seed: u64, difficulty: Difficulty){
+        let start_x = (self.width / 2) - 11;
+        let start_y = (self.height / 2) - 4;
+        self.frame.set_color(color::BLACK, color::BLUE);
+        rect(&mut self.frame, start_x - 1, start_y -1, start_x + 22, start_y + 8);
+        self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+        rect(&mut self.frame, start_x, start_y, start_x + 21, start_y + 7);
+        self.frame.set_cursor_position(start_x + 2, start_y + 1);
+        self.frame.write("Game Over!");
+        self.frame.set_cursor_position(start_x + 2, start_y + 2);
+        self.frame.write(&format!("Time: {}", time / 1000));
+        self.frame.set_cursor_position(start_x + 2, start_y + 3);
+        self.frame.write(&format!("Seed: {}", seed));
+        self.frame.set_cursor_position(start_x + 2, start_y + 4);
+        self.frame.write(&format!("Difficulty: {}", difficulty.label()));
+        self.frame.set_cursor_position(start_x + 2, start_y + 5);
+        self.frame.write(&format!("{} = Restart", self.keys.key_name(Action::Restart)));
+        self.frame.set_cursor_position(start_x + 2, start_y + 6);
+        self.frame.write(&format!("{} = Quit", self.keys.key_name(Action::Quit)));
+    }
+
+    ///A box in the middle of the field listing `items`, with the selected one highlighted
+    pub fn draw_menu_box(&mut self, title: &str, items: &[&str], selected: usize){
+        let width = items.iter().map(|item| item.len()).max().unwrap_or(0).max(title.len()) as i16 + 6;
+        let height = items.len() as i16 + 3;
+        let start_x = (self.width / 2) - (width / 2);
+        let start_y = (self.height / 2) - (height / 2);
+        self.frame.set_color(color::BLACK, color::BLUE);
+        rect(&mut self.frame, start_x - 1, start_y - 1, start_x + width + 1, start_y + height + 1);
+        self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+        rect(&mut self.frame, start_x, start_y, start_x + width, start_y + height);
+        self.frame.set_cursor_position(start_x + 2, start_y + 1);
+        self.frame.write(title);
+
+        for (i, item) in items.iter().enumerate(){
+            if i == selected{
+                self.frame.set_color(color::WHITE, color::DARK_BLUE);
+            }else{
+                self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+            }
+            self.frame.set_cursor_position(start_x + 2, start_y + 3 + i as i16);
+            self.frame.write(&format!(" {} ", item));
+        }
+    }
+
+    ///The key binding screen, `waiting` is set while a new key is being picked
+    pub fn draw_options(&mut self, keys: &KeyMap, selected: usize, waiting: bool){
+        let total_height = self.total_height();
+        draw_main_menu_top(&mut self.frame, self.width, total_height);
+        self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+        draw_centered_string(&mut self.frame, self.width, "Options", 6);
+        draw_centered_string(&mut self.frame, self.width, "Controls:", 8);
+
+        let x = (self.width / 2) - 15;
+        for (i, action) in ACTIONS.iter().enumerate(){
+            if i == selected{
+                self.frame.set_color(color::WHITE, color::DARK_BLUE);
+            }else{
+                self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+            }
+            self.frame.set_cursor_position(x, 10 + i as i16);
+            self.frame.write(&format!(" {:<12}{:<17}", action.label(), keys.describe(*action)));
+        }
+
+        self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+        if waiting{
+            draw_centered_string(&mut self.frame, self.width, &format!("Press a key for {} (Escape Cancels)",
+                ACTIONS[selected].label()), 11 + ACTIONS.len() as i16);
+        }
+        draw_centered_string(&mut self.frame, self.width, "Enter = Rebind   1 = Arrows   2 = WASD   3 = hjkl",
+            total_height - 7);
+        draw_centered_string(&mut self.frame, self.width, "To Save and Go Back Press Escape", total_height - 6);
+    }
+
+    pub fn draw_name_entry(&mut self, name: &str){
+        let start_x = (self.width / 2) - 11;
+        let start_y = (self.height / 2) - 3;
+        self.frame.set_color(color::BLACK, color::BLUE);
+        rect(&mut self.frame, start_x - 1, start_y -1, start_x + 22, start_y + 6);
+        self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+        rect(&mut self.frame, start_x, start_y, start_x + 21, start_y + 5);
+        self.frame.set_cursor_position(start_x + 2, start_y + 1);
+        self.frame.write("New High Score!");
+        self.frame.set_cursor_position(start_x + 2, start_y + 2);
+        self.frame.write(&format!("Name: {}_", name));
+        self.frame.set_cursor_position(start_x + 2, start_y + 4);
+        self.frame.write("Enter = Save");
+    }
+
+    pub fn draw_high_scores(&mut self, scores: &[HighScore]){
+        let total_height = self.total_height();
+        draw_main_menu_top(&mut self.frame, self.width, total_height);
+        self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+        draw_centered_string(&mut self.frame, self.width, "High Scores", 6);
+
+        let header = format!("{:<3}{:<14}{:>6}{:>10}{:>10}{:>10}  {:<11}{:<10}",
+            "#", "Name", "Time", "Powerups", "Ash Rate", "Seed", "Date", "Difficulty");
+        let x = (self.width / 2) - (header.len() as i16 / 2);
+        self.frame.set_cursor_position(x, 8);
+        self.frame.write(&header);
+
+        for (i, score) in scores.iter().enumerate(){
+            self.frame.set_cursor_position(x, 10 + i as i16);
+            self.frame.write(&format!("{:<3}{:<14}{:>6}{:>10}{:>10}{:>10}  {:<11}{:<10}",
+                i + 1, score.name, score.time / 1000, score.powerups, score.peak_ash_rate, score.seed, score.date,
+                score.difficulty.label()));
+        }
+
+        if scores.is_empty(){
+            draw_centered_string(&mut self.frame, self.width, "No runs yet!", 10);
+        }
+
+        draw_centered_string(&mut self.frame, self.width, "To Go Back Press Escape", total_height - 6);
+    }
+
+    ///Shows `notice` on the main menu until it is set to something else
+    pub fn set_notice(&mut self, notice: &str){
+        self.notice = notice.to_string();
     }
 
     pub fn set_status(&mut self, status: &str){
         self.status = status.to_string();
-        draw_status_bar(&mut self.console, &self.status);
+        draw_status_bar(&mut self.frame, self.width, self.height, &self.status);
     }
 
     pub fn draw_powerups(&mut self, field: &Field){
-        for x in 0..consts::WORLD_WIDTH{
-            for y in 0..consts::WORLD_HEIGHT{
-                if let Some(powerup) = field[x as usize][y as usize]{
-                    match powerup {
-                        Powerup::Charge => draw_charge(&mut self.console, x, y),
-                        Powerup::Destroyed => draw_ash(&mut self.console, x, y),
-                        Powerup::Cleanup => draw_cleanup(&mut self.console, x, y),
-                        Powerup::Reset => draw_reset(&mut self.console, x, y),
-                    }
-                }
+        let (origin_x, origin_y) = self.camera.origin();
+
+        for x in 0..self.camera.width(){
+            for y in 0..self.camera.height(){
+                self.draw_cell(field, origin_x + x, origin_y + y);
+            }
+        }
+
+        for falling in &self.falling{
+            if let Some((x, y)) = self.camera.to_view(falling.x, falling.y){
+                let ground = self.ground(falling.x, falling.y);
+                draw_falling(&mut self.frame, x, y, falling.left, ground);
+            }
+        }
+
+        if let Some(volcano) = self.volcano{
+            if let Some((x, y)) = self.camera.to_view(volcano.x, volcano.y){
+                draw_volcano(&mut self.frame, x, y, volcano.phase);
             }
         }
     }
 
+    ///Draws whatever is on the field cell `x, y`, which has to be in view.
+    ///Powerups lying on the brown ground lie on the smoke shade instead.
+    fn draw_cell(&mut self, field: &Field, x: i16, y: i16){
+        if let Some(powerup) = field.get(x, y){
+            let kind = powerups::kind(powerup);
+            let background = if kind.background == color::BROWN { self.ground(x, y) }else{ kind.background };
+            let (view_x, view_y) = (x - self.camera.origin().0, y - self.camera.origin().1);
+            self.frame.set_color(kind.foreground, background);
+            self.frame.set_cursor_position(view_x, view_y + consts::FIELD_TOP);
+            self.frame.write(kind.glyph);
+        }
+    }
+
+    ///The background of the field cell `x, y`, darker the thicker the smoke over it
+    fn ground(&self, x: i16, y: i16) -> u16{
+        match self.smoke{
+            Some(ref smoke) => smoke_shade(smoke.get(x, y)),
+            None => color::BROWN,
+        }
+    }
+
+    ///Brings the camera round to the player and repaints the empty ground
+    ///of the whole view, so draw the player and the powerups after it
+    pub fn draw_ground(&mut self, player: &Position){
+        self.camera.follow(player.x, player.y - 1);
+        let (origin_x, origin_y) = self.camera.origin();
+
+        for x in 0..self.camera.width(){
+            for y in 0..self.camera.height(){
+                let ground = self.ground(origin_x + x, origin_y + y);
+                self.frame.set_color(ground, ground);
+                self.frame.write_character(x, y + consts::FIELD_TOP, 32);
+            }
+        }
+    }
+
+    ///The smoke to shade the ground with from now on
+    pub fn set_smoke(&mut self, smoke: &Smoke){
+        self.smoke = Some(smoke.clone());
+    }
+
+    ///The volcano to draw with the powerups from now on
+    pub fn set_volcano(&mut self, volcano: &Volcano){
+        self.volcano = Some(*volcano);
+    }
+
+    ///The falling ash to draw with the powerups from now on
+    pub fn set_falling(&mut self, falling: &[Falling]){
+        self.falling = falling.to_vec();
+    }
+
     pub fn draw_menu_ui(&mut self, time: u64, ash_rate: u16, powerups: u16){
-        draw_stats(&mut self.console, time, ash_rate, powerups);
-        draw_instructions(&mut self.console);
-        draw_title(&mut self.console);
-        self.console.set_cursor_position(0,0);
+        draw_stats(&mut self.frame, time, ash_rate, powerups);
+        draw_instructions(&mut self.frame, self.width);
+        draw_title(&mut self.frame, self.width, &self.keys);
+        self.frame.set_cursor_position(0,0);
     }
 
-    pub fn remove_ash(&mut self, x: i16, y: i16){
-        self.console.set_color(color::BROWN, color::BROWN);
-        self.console.write_character(x, y + consts::MENU_HEIGHT + 1, 32);
+    ///Sits between the stats and the instructions, left out when the screen
+    ///is too narrow for it
+    pub fn draw_minimap(&mut self, field: &Field, player: &Position){
+        let x = self.width - 33 - MINIMAP_WIDTH;
+
+        if x >= 25{
+            minimap::draw_minimap(&mut self.frame, x, 1, field, player);
+            self.frame.set_cursor_position(0,0);
+        }
     }
 
-    pub fn update_charge(&mut self, charge: i16){
-        self.console.set_color(color::BLACK, color::LIGHT_GRAY);
-        rect(&mut self.console, 0, consts::WORLD_HEIGHT + 1, consts::WORLD_WIDTH, consts::WORLD_HEIGHT + 2);
-        self.console.set_color(color::WHITE, color::YELLOW);
-        rect(&mut self.console, 0, consts::WORLD_HEIGHT + 1, charge * 2, consts::WORLD_HEIGHT + 2);
-        self.console.set_color(color::BLACK, color::YELLOW);
-        self.console.set_cursor_position(0, consts::WORLD_HEIGHT + 1);
-        self.console.write("Power\nLevel");
-        self.console.set_cursor_position(0,0);
+    ///Lists the effects on the player between the stats and the minimap,
+    ///or the instructions when there is no room for the minimap
+    pub fn draw_effects(&mut self, effects: &Effects){
+        let minimap = self.width - 33 - MINIMAP_WIDTH;
+        let right = if minimap >= 25 { minimap - 2 }else{ self.width - 33 };
+
+        draw_effects(&mut self.frame, 26, right, effects.active());
+        self.frame.set_cursor_position(0,0);
+    }
+
+    ///Empties the field cell `x, y`
+    pub fn clear_cell(&mut self, x: i16, y: i16){
+        let ground = self.ground(x, y);
+        if let Some((x, y)) = self.camera.to_view(x, y){
+            self.frame.set_color(ground, ground);
+            self.frame.write_character(x, y + consts::FIELD_TOP, 32);
+        }
+    }
+
+    ///The charge in yellow, the rest of the capacity in gray and what magma
+    ///has crimpled away in red after it, two columns to a unit
+    pub fn update_charge(&mut self, battery: &Battery){
+        let (top, bottom) = (self.height + 1, self.height + 2);
+        let charge = cmp::min(battery.charge * 2, self.width);
+        let capacity = cmp::min(battery.capacity * 2, self.width);
+        let lost = cmp::min((battery.capacity + battery.lost) * 2, self.width);
+
+        self.frame.set_color(color::BLACK, color::LIGHT_GRAY);
+        rect(&mut self.frame, 0, top, self.width, bottom);
+        if battery.lost > 0{
+            self.frame.set_color(color::BLACK, color::DARK_RED);
+            rect(&mut self.frame, capacity, top, lost, bottom);
+        }
+        self.frame.set_color(color::BLACK, color::DARK_GRAY);
+        rect(&mut self.frame, charge, top, capacity, bottom);
+        self.frame.set_color(color::WHITE, color::YELLOW);
+        rect(&mut self.frame, 0, top, charge, bottom);
+        self.frame.set_color(color::BLACK, color::YELLOW);
+        self.frame.set_cursor_position(0, top);
+        self.frame.write(&format!("Power {}/{}\nHealth {}%", battery.charge, battery.capacity, battery.health()));
+        self.frame.set_cursor_position(0,0);
     }
 
+    ///Also brings the camera round to the player, so draw them before the powerups
     pub fn draw_player(&mut self, player: &Position){
-        self.console.set_color(color::BLUE, color::BROWN);
-        self.console.set_cursor_position(player.x, player.y + consts::MENU_HEIGHT);
-        self.console.write("@");
-        self.console.set_cursor_position(0,0);
+        self.camera.follow(player.x, player.y - 1);
+
+        let ground = self.ground(player.x, player.y - 1);
+        if let Some((x, y)) = self.camera.to_view(player.x, player.y - 1){
+            self.frame.set_color(color::BLUE, ground);
+            self.frame.set_cursor_position(x, y + consts::FIELD_TOP);
+            self.frame.write("@");
+            self.frame.set_cursor_position(0,0);
+        }
     }
 
-    pub fn move_player(&mut self, player: &Position, prev_player_pos: &Position){
-        self.console.set_color(color::DARK_RED, color::BROWN);
-        self.console.write_character(prev_player_pos.x,
-            prev_player_pos.y + consts::MENU_HEIGHT, 32);
+    ///Repaints the whole view when the camera has to scroll after the player
+    pub fn move_player(&mut self, player: &Position, prev_player_pos: &Position, field: &Field){
+        if self.camera.follow(player.x, player.y - 1){
+            self.draw_ground(player);
+            self.draw_player(player);
+            self.draw_powerups(field);
+            return;
+        }
+
+        let (x, y) = (prev_player_pos.x, prev_player_pos.y - 1);
+        if self.camera.to_view(x, y).is_some(){
+            self.clear_cell(x, y);
+            self.draw_cell(field, x, y);
+        }
         self.draw_player(player);
     }
 }
 
-pub fn draw_main_menu_center(console: &mut Console){
+pub fn draw_main_menu_center<R: Renderer>(console: &mut R, width: i16, total_height: i16, keys: &KeyMap){
     console.set_color(color::BLACK, color::LIGHT_GRAY);
-    draw_centered_string(console, "In a world far in the future a series of events causes massive", 7);
-    draw_centered_string(console, "volcanic events. Nearby a volcano, Kuklonfusta, you hear an", 8);
-    draw_centered_string(console, "eruption begin ! Kuklonfust puffs out smoke covering the sky,", 9);
-    draw_centered_string(console, "disabling all solar panels in the area. You must keep on collecting", 10);
-    draw_centered_string(console, "energy to stay alive! But beware! The volcanic magma is starting to fall...", 11);
-    draw_centered_string(console, "Controls:", consts::TOTAL_HEIGHT - 10);
-    draw_centered_string(console, "Use Arrow Keys To Move", consts::TOTAL_HEIGHT - 9);
-    draw_centered_string(console, "Move over powerups to use them", consts::TOTAL_HEIGHT - 8);
-    draw_centered_string(console, "To Start the Game Press Enter", consts::TOTAL_HEIGHT - 7);
-    draw_centered_string(console, "To Quit the Game Press Escape", consts::TOTAL_HEIGHT - 6);
-}
-
-pub fn draw_main_menu_info(console: &mut Console){
+    draw_centered_string(console, width, "Controls:", total_height - 10);
+    draw_centered_string(console, width, &format!("Use {}/{}/{}/{} To Move", keys.key_name(Action::MoveUp),
+        keys.key_name(Action::MoveLeft), keys.key_name(Action::MoveDown), keys.key_name(Action::MoveRight)),
+        total_height - 9);
+    draw_centered_string(console, width, &format!("Move over powerups to use them, {} puts down a solar panel",
+        keys.key_name(Action::Confirm)), total_height - 8);
+    draw_centered_string(console, width, &format!("To Start the Game Press {}",
+        keys.key_name(Action::Confirm)), total_height - 7);
+    draw_centered_string(console, width, &format!("To Quit the Game Press {}",
+        keys.key_name(Action::Quit)), total_height - 6);
+    draw_centered_string(console, width, &format!("To View High Scores Press {}, For Options Press {}",
+        keys.key_name(Action::HighScores), keys.key_name(Action::Options)), total_height - 5);
+}
+
+///The story and the powerup list, between the title and the difficulty.
+///When the console is too short for both the story is left out, and
+///whatever of the list still does not fit is cut off. Lines wider than
+///the menu are left out too.
+pub fn draw_main_menu_info<R: Renderer>(console: &mut R, width: i16, total_height: i16){
+    let story = [
+        "In a world far in the future a series of events causes massive",
+        "volcanic events. Nearby a volcano, Kuklonfusta, you hear an",
+        "eruption begin ! Kuklonfust puffs out smoke covering the sky,",
+        "disabling all solar panels in the area. You must keep on collecting",
+        "energy to stay alive! But beware! The volcanic magma is starting to fall...",
+    ];
+    let mut info = vec!["Powerups:"];
+    info.extend(KINDS.iter().map(|kind| kind.description).filter(|description| !description.is_empty()));
+    info.extend_from_slice(&["░: Falling ash, move away before it lands",
+        "Smoke from the volcano darkens the ground and blocks the sun", "",
+        "Note: Magma halves your power and crimples your battery for good!"]);
+    info.retain(|line| line.chars().count() as i16 <= width - 2);
+
+    let last = total_height - 14;
+    let fits = |top: i16| top + info.len() as i16 - 1 <= last;
+    let (story_top, info_top) = if fits(15){
+        (Some(7), 15)
+    }else if fits(11){
+        (Some(5), 11)
+    }else{
+        (None, 5)
+    };
+
     console.set_color(color::BLACK, color::LIGHT_GRAY);
-    draw_centered_string(console,
-        "Powerups:", 15);
-    draw_centered_string(console,
-        "₽: Increases the power in your battery (Bottom of the Screen)", 16);
-    draw_centered_string(console,
-        "C: Removes 66% of the magama. But it will double the rate the volcano spews lava", 17);
-    draw_centered_string(console,
-        "R: Calms the volcano", 18);
-    draw_centered_string(console,
-        "Note: Walking on Magma crimples your battery!", 21);
-}
-
-pub fn draw_centered_string(console: &mut Console, string: &str, y: i16){
+    if let Some(top) = story_top{
+        for (i, line) in story.iter().enumerate(){
+            draw_centered_string(console, width, line, top + i as i16);
+        }
+    }
+    for (y, line) in (info_top..last + 1).zip(info.iter()){
+        draw_centered_string(console, width, line, y);
+    }
+}
+
+pub fn draw_centered_string<R: Renderer>(console: &mut R, width: i16, string: &str, y: i16){
     let chars: Vec<char> = string.chars().collect();
-    let width = consts::WORLD_WIDTH;
     console.set_cursor_position((width / 2) - ((chars.len() / 2) as i16), y);
     console.write(string);
 }
 
-pub fn draw_main_menu_top(console: &mut Console){
+pub fn draw_main_menu_top<R: Renderer>(console: &mut R, width: i16, total_height: i16){
     console.set_color(color::BLACK, color::BLUE);
-    rect(console, 0, 0, consts::WORLD_WIDTH, consts::TOTAL_HEIGHT);
+    rect(console, 0, 0, width, total_height);
     console.set_color(color::BLACK, color::LIGHT_GRAY);
-    rect(console, 1, 1, consts::WORLD_WIDTH - 1, consts::TOTAL_HEIGHT - 4);
-    draw_centered_string(console, "Ash Dash", 2);
-    draw_centered_string(console, "A Game for LD 39", 3);
+    rect(console, 1, 1, width - 1, total_height - 4);
+    draw_centered_string(console, width, "Ash Dash", 2);
+    draw_centered_string(console, width, "A Game for LD 39", 3);
     console.set_color(color::BLACK, color::DARK_GRAY);
 }
 
-pub fn draw_stats(console: &mut Console, time: u64, ash_rate: u16, powerups: u16){
+pub fn draw_stats<R: Renderer>(console: &mut R, time: u64, ash_rate: u16, powerups: u16){
     console.set_color(color::BLACK, color::LIGHT_GRAY);
     console.set_cursor_position(0,1);
     console.write(&format!("Time: {} \nAsh Rate: {}\nPowerups Collected: {}", time / 1000, ash_rate, powerups));
 }
 
-pub fn draw_title(console: &mut Console){
+///One effect a row from `x1`, in columns as wide as `EFFECT_WIDTH` that
+///stop before `x2`. Whatever does not fit is left off.
+pub fn draw_effects<R: Renderer>(console: &mut R, x1: i16, x2: i16, active: &[Status]){
+    console.set_color(color::BLACK, color::LIGHT_GRAY);
+    if x2 < x1 + EFFECT_WIDTH{
+        return;
+    }
+    rect(console, x1, 1, x2, 3);
+
+    let columns = ((x2 - x1 + 1) / EFFECT_WIDTH) as usize;
+    for (i, status) in active.iter().take(columns * 3).enumerate(){
+        let stacks = if status.stacks > 1 { format!(" x{}", status.stacks) }else{ String::new() };
+        console.set_cursor_position(x1 + (i / 3) as i16 * EFFECT_WIDTH, 1 + (i % 3) as i16);
+        console.write(&format!("{}{} {}s", status.effect.label(), stacks, status.left.div_ceil(1000)));
+    }
+}
+
+pub fn draw_title<R: Renderer>(console: &mut R, width: i16, keys: &KeyMap){
+    let hint = format!("Press {} To Pause The Game", keys.key_name(Action::Pause));
     console.set_color(color::RED, color::LIGHT_GRAY);
-    console.set_cursor_position((consts::WORLD_WIDTH / 2) - 4, 0);
+    console.set_cursor_position((width / 2) - 4, 0);
     console.write("ASH DASH");
-    console.set_cursor_position((consts::WORLD_WIDTH / 2) - 15, consts::MENU_HEIGHT - 1);
-    console.write("Press Escape To Close The Game");
+    console.set_cursor_position((width / 2) - (hint.len() as i16 / 2), consts::MENU_HEIGHT - 1);
+    console.write(&hint);
 }
 
-pub fn draw_instructions(console: &mut Console){
-    console.set_cursor_position(consts::WORLD_WIDTH - 31, 0);
+pub fn draw_instructions<R: Renderer>(console: &mut R, width: i16){
+    console.set_cursor_position(width - 31, 0);
     console.write("Collect Cs to cleanup the Lava!");
-    console.set_cursor_position(consts::WORLD_WIDTH - 31, 1);
+    console.set_cursor_position(width - 31, 1);
     console.write("Collect ₽s to gain more power!");
-    console.set_cursor_position(consts::WORLD_WIDTH - 31, 2);
+    console.set_cursor_position(width - 31, 2);
     console.write("Collect Rs to slow the volcano!");
-    console.set_cursor_position(consts::WORLD_WIDTH - 31, 4);
+    console.set_cursor_position(width - 31, 4);
     console.write("Lava cuts you battery in half!");
-    //console.set_cursor_position(consts::WORLD_WIDTH - 37, 5);
-}
-
-fn draw_charge(console: &mut Console, x: i16, y: i16){
-    console.set_cursor_position(x, y + consts::MENU_HEIGHT + 1);
-    console.set_color(color::YELLOW, color::BROWN);
-    console.write("₽");
+    //console.set_cursor_position(width - 37, 5);
 }
 
-fn draw_reset(console: &mut Console, x: i16, y: i16){
-    console.set_cursor_position(x, y + consts::MENU_HEIGHT + 1);
-    console.set_color(color::YELLOW, color::BROWN);
-    console.write("R");
+fn draw_volcano<R: Renderer>(console: &mut R, x: i16, y: i16, phase: Phase){
+    let foreground = match phase{
+        Phase::Dormant => color::DARK_GRAY,
+        Phase::Rumbling => color::YELLOW,
+        Phase::Erupting => color::RED,
+        Phase::Cooling => color::DARK_RED,
+    };
+    console.set_cursor_position(x, y + consts::FIELD_TOP);
+    console.set_color(foreground, color::BLACK);
+    console.write("^");
 }
 
-fn draw_cleanup(console: &mut Console, x: i16, y: i16){
-    console.set_cursor_position(x, y + consts::MENU_HEIGHT + 1);
-    console.set_color(color::YELLOW, color::BROWN);
-    console.write("C");
+///The shadow of falling ash, redder the closer it is to landing and lighter
+///when smoke darkens the ground
+fn draw_falling<R: Renderer>(console: &mut R, x: i16, y: i16, left: u32, ground: u16){
+    let foreground = match (left, ground){
+        (0..=1, _) => color::RED,
+        (_, color::BROWN) => color::DARK_GRAY,
+        _ => color::LIGHT_GRAY,
+    };
+    console.set_cursor_position(x, y + consts::FIELD_TOP);
+    console.set_color(foreground, ground);
+    console.write("░");
 }
 
-fn draw_ash(console: &mut Console, x: i16, y: i16){
-    //console.set_cursor_position(x, y + consts::MENU_HEIGHT + 1);
-    console.set_color(color::DARK_RED, color::DARK_RED);
-    console.write_character(x, y + consts::MENU_HEIGHT + 1, 32)
+///Clear sky leaves the ground brown, thin smoke grays it and thick smoke blacks it out
+fn smoke_shade(density: u8) -> u16{
+    match density{
+        d if d < CLEAR_DENSITY => color::BROWN,
+        d if d < 5 => color::DARK_GRAY,
+        _ => color::BLACK,
+    }
 }
 
-fn draw_field(console: &mut Console){
+fn draw_field<R: Renderer>(console: &mut R, width: i16, height: i16){
     console.set_color(color::BLACK, color::BROWN);
-    rect(console, 0, 0, consts::WORLD_WIDTH, consts::WORLD_HEIGHT);
+    rect(console, 0, 0, width, height);
 }
 
-fn draw_status_bar(console: &mut Console, message: &str){
-    let height = consts::WORLD_HEIGHT;
-    let width = consts::WORLD_WIDTH;
+fn draw_status_bar<R: Renderer>(console: &mut R, width: i16, height: i16, message: &str){
 
     console.set_color(color::BLACK, color::LIGHT_GRAY);
     rect(console, 0, height + 1, width, height + consts::STATUS_BAR_HEIGHT);
@@ -227,9 +600,7 @@ fn draw_status_bar(console: &mut Console, message: &str){
     console.write(message);
 }
 
-fn draw_menu(console: &mut Console){
-    let width = consts::WORLD_WIDTH;
-
+fn draw_menu<R: Renderer>(console: &mut R, width: i16){
     console.set_color(color::BLACK, color::DARK_BLUE);
     rect(console, 0, consts::MENU_HEIGHT, width, consts::MENU_HEIGHT);
 
@@ -0,0 +1,42 @@
+#[cfg(windows)]
+use cannon::Console;
+#[cfg(unix)]
+use ansi::Console;
+
+///The drawing primitives `Graphics` needs from a console
+pub trait Renderer{
+    fn set_color(&mut self, foreground: u16, background: u16);
+    fn set_cursor_position(&mut self, x: i16, y: i16);
+    fn write(&mut self, string: &str);
+    fn write_character(&mut self, x: i16, y: i16, character: u8);
+    fn clear_screen(&mut self);
+    ///Makes sure everything written so far is on screen
+    fn flush(&mut self){}
+}
+
+impl Renderer for Console{
+    fn set_color(&mut self, foreground: u16, background: u16){
+        Console::set_color(self, foreground, background);
+    }
+
+    fn set_cursor_position(&mut self, x: i16, y: i16){
+        Console::set_cursor_position(self, x, y);
+    }
+
+    fn write(&mut self, string: &str){
+        Console::write(self, string);
+    }
+
+    fn write_character(&mut self, x: i16, y: i16, character: u8){
+        Console::write_character(self, x, y, character);
+    }
+
+    fn clear_screen(&mut self){
+        Console::clear_screen(self);
+    }
+
+    #[cfg(unix)]
+    fn flush(&mut self){
+        Console::flush(self);
+    }
+}
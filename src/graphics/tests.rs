@@ -0,0 +1,233 @@
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write};
+use super::Graphics;
+use super::consts;
+use super::framebuffer::Framebuffer;
+use Powerup;
+use field::Field;
+use highscore::HighScore;
+use rules::Difficulty;
+use bindings::{KeyMap, Preset};
+use util::Position;
+use battery::Battery;
+use smoke::Smoke;
+use effects::{Effect, Effects};
+
+///Flushes the graphics and compares what reached the console against
+///`snapshots/<name>.snap`. Run the tests with UPDATE_SNAPSHOTS=1 to rewrite
+///the files after an intended change.
+fn assert_snapshot(name: &str, graphics: &mut Graphics<Framebuffer>){
+    graphics.flush();
+    let framebuffer = &graphics.console;
+    let path = format!("{}/src/graphics/snapshots/{}.snap", env!("CARGO_MANIFEST_DIR"), name);
+    let actual = framebuffer.snapshot();
+
+    if env::var("UPDATE_SNAPSHOTS").is_ok(){
+        File::create(&path).and_then(|mut f| f.write_all(actual.as_bytes()))
+            .unwrap_or_else(|err| panic!("Could not write snapshot {}: {}", path, err));
+        return;
+    }
+
+    let mut expected = String::new();
+    File::open(&path).and_then(|mut f| f.read_to_string(&mut expected))
+        .unwrap_or_else(|err| panic!("Could not read snapshot {}: {}", path, err));
+
+    for (line, (a, e)) in actual.lines().zip(expected.lines()).enumerate(){
+        assert!(a == e, "Snapshot {} differs at line {}:\nexpected: {}\nactual:   {}", name, line + 1, e, a);
+    }
+    assert_eq!(actual.lines().count(), expected.lines().count(), "Snapshot {} has a different size", name);
+}
+
+fn graphics() -> Graphics<Framebuffer>{
+    Graphics::new(Framebuffer::new())
+}
+
+fn field() -> Field{
+    let mut field = Field::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT);
+    field.set(3, 3, Some(Powerup::Charge));
+    field.set(10, 5, Some(Powerup::Cleanup));
+    field.set(20, 8, Some(Powerup::Reset));
+    field.set(30, 12, Some(Powerup::Destroyed));
+    field.set(31, 12, Some(Powerup::Destroyed));
+    field.set(95, 10, Some(Powerup::Charge));
+    field
+}
+
+#[test]
+fn main_menu(){
+    let mut graphics = graphics();
+    graphics.draw_main_menu(false, Difficulty::Normal);
+    assert_snapshot("main_menu", &mut graphics);
+}
+
+#[test]
+fn main_menu_on_a_small_console(){
+    let mut graphics = Graphics::new(Framebuffer::with_size(80, 24));
+    graphics.set_view_size(79, 12);
+    graphics.set_field_size(consts::FIELD_WIDTH, consts::FIELD_HEIGHT);
+    graphics.draw_main_menu(true, Difficulty::Normal);
+    assert_snapshot("main_menu_small", &mut graphics);
+}
+
+#[test]
+fn main_menu_shows_the_chosen_keys(){
+    let mut graphics = graphics();
+    graphics.set_keys(&KeyMap::preset(Preset::Wasd));
+    graphics.draw_main_menu(false, Difficulty::Normal);
+    graphics.flush();
+
+    assert!(graphics.console.snapshot().contains("Use W/A/S/D To Move"));
+}
+
+#[test]
+fn main_menu_shows_the_notice(){
+    let mut graphics = graphics();
+    graphics.set_notice("Could not load the saved game: the field is too big");
+    graphics.draw_main_menu(false, Difficulty::Normal);
+    graphics.flush();
+    assert!(graphics.console.snapshot().contains("Could not load the saved game: the field is too big"));
+
+    graphics.set_notice("");
+    graphics.draw_main_menu(false, Difficulty::Normal);
+    graphics.flush();
+    assert!(!graphics.console.snapshot().contains("Could not load"));
+}
+
+#[test]
+fn main_menu_with_save(){
+    let mut graphics = graphics();
+    graphics.draw_main_menu(true, Difficulty::Hard);
+    assert_snapshot("main_menu_with_save", &mut graphics);
+}
+
+#[test]
+fn background(){
+    let mut graphics = graphics();
+    graphics.redraw_background();
+    assert_snapshot("background", &mut graphics);
+}
+
+#[test]
+fn powerups(){
+    let mut graphics = graphics();
+    graphics.redraw_background();
+    graphics.draw_powerups(&field());
+    assert_snapshot("powerups", &mut graphics);
+}
+
+#[test]
+fn charge(){
+    let mut graphics = graphics();
+    graphics.redraw_background();
+    let mut battery = Battery::new(35, 70, 2);
+    battery.hit();
+    graphics.update_charge(&battery);
+    assert_snapshot("charge", &mut graphics);
+}
+
+#[test]
+fn end_game(){
+    let mut graphics = graphics();
+    graphics.redraw_background();
+    graphics.draw_end_game(125000, 4242, Difficulty::Nightmare);
+    assert_snapshot("end_game", &mut graphics);
+}
+
+#[test]
+fn pause_menu(){
+    let mut graphics = graphics();
+    graphics.redraw_background();
+    graphics.draw_menu_box("Paused", &["Resume", "Restart", "Options", "Quit to Menu"], 1);
+    assert_snapshot("pause_menu", &mut graphics);
+}
+
+#[test]
+fn options(){
+    let mut graphics = graphics();
+    graphics.draw_options(&KeyMap::preset(Preset::Wasd), 2, true);
+    assert_snapshot("options", &mut graphics);
+}
+
+#[test]
+fn high_scores(){
+    let mut graphics = graphics();
+    let scores = vec![
+        HighScore {name: "Lava Surfer".to_string(), time: 95000, powerups: 41, peak_ash_rate: 30, seed: 4242, date: "2017-07-30".to_string(),
+            difficulty: Difficulty::Hard},
+        HighScore {name: "DevOrc".to_string(), time: 61000, powerups: 22, peak_ash_rate: 18, seed: 77, date: "2017-07-29".to_string(),
+            difficulty: Difficulty::Normal},
+    ];
+    graphics.draw_high_scores(&scores);
+    assert_snapshot("high_scores", &mut graphics);
+}
+
+#[test]
+fn scrolled_view(){
+    let mut graphics = Graphics::new(Framebuffer::with_size(81, 40));
+    graphics.set_view_size(80, 20);
+    graphics.set_field_size(consts::FIELD_WIDTH, consts::FIELD_HEIGHT);
+    graphics.redraw_background();
+    graphics.draw_player(&Position {x: 100, y: 13});
+    graphics.draw_powerups(&field());
+    graphics.move_player(&Position {x: 101, y: 13}, &Position {x: 100, y: 13}, &field());
+    assert_snapshot("scrolled_view", &mut graphics);
+}
+
+#[test]
+fn minimap(){
+    let mut graphics = graphics();
+    let mut field = field();
+    for x in 100..120{
+        for y in 20..30{
+            field.set(x, y, Some(Powerup::Destroyed));
+        }
+    }
+    graphics.redraw_background();
+    graphics.draw_menu_ui(42000, 7, 3);
+    graphics.draw_minimap(&field, &Position {x: 15, y: 15});
+    assert_snapshot("minimap", &mut graphics);
+}
+
+#[test]
+fn effects(){
+    let mut graphics = graphics();
+    let mut effects = Effects::new();
+    effects.add(Effect::Shield);
+    effects.add(Effect::Shield);
+    effects.add(Effect::Frozen);
+    effects.add(Effect::Magnet);
+    effects.add(Effect::Scorched);
+    effects.tick(1500);
+    graphics.redraw_background();
+    graphics.draw_menu_ui(42000, 7, 3);
+    graphics.draw_minimap(&field(), &Position {x: 15, y: 15});
+    graphics.draw_effects(&effects);
+    assert_snapshot("effects", &mut graphics);
+}
+
+#[test]
+fn smoke(){
+    let mut graphics = graphics();
+    let mut smoke = Smoke::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT);
+    smoke.puff(12, 6, 3, 3);
+    smoke.puff(12, 6, 1, 6);
+    let player = Position {x: 11, y: 7};
+    graphics.redraw_background();
+    graphics.set_smoke(&smoke);
+    graphics.draw_ground(&player);
+    graphics.draw_player(&player);
+    graphics.draw_powerups(&field());
+    assert_snapshot("smoke", &mut graphics);
+}
+
+#[test]
+fn flush_only_sends_changes(){
+    let mut graphics = graphics();
+    graphics.redraw_background();
+    graphics.flush();
+
+    graphics.console = Framebuffer::new();
+    graphics.draw_powerups(&field());
+    assert_snapshot("flush_only_sends_changes", &mut graphics);
+}
@@ -1,8 +1,6 @@
-extern crate cannon;
+use super::renderer::Renderer;
 
-use cannon::*;
-
-pub fn rect(console: &mut Console, x1: i16, y1: i16, x2: i16, y2: i16){
+pub fn rect<R: Renderer>(console: &mut R, x1: i16, y1: i16, x2: i16, y2: i16){
     let width = x2 - x1;
     let height = y2 - y1;
 
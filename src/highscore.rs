@@ -0,0 +1,134 @@
+use std::cmp;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use rules::Difficulty;
+use util::data_dir;
+
+///How many runs the table keeps
+pub const MAX_SCORES: usize = 10;
+pub const MAX_NAME_LENGTH: usize = 12;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScore{
+    pub name: String,
+    ///Milliseconds survived
+    pub time: u64,
+    pub powerups: u16,
+    pub peak_ash_rate: u16,
+    pub seed: u64,
+    pub date: String,
+    pub difficulty: Difficulty
+}
+
+///The best runs, longest survival first
+pub struct HighScores{
+    pub scores: Vec<HighScore>
+}
+
+impl HighScores{
+    pub fn path() -> PathBuf{
+        data_dir().join("highscores.txt")
+    }
+
+    ///Loads the table, starting a new one if there is none yet
+    pub fn load() -> HighScores{
+        let mut scores = Vec::new();
+
+        if let Ok(file) = File::open(HighScores::path()){
+            for line in BufReader::new(file).lines(){
+                if let Some(score) = line.ok().as_ref().and_then(|l| parse_score(l)){
+                    scores.push(score);
+                }
+            }
+        }
+
+        let mut high_scores = HighScores {scores: scores};
+        high_scores.sort();
+        high_scores
+    }
+
+    pub fn save(&self) -> io::Result<()>{
+        fs::create_dir_all(data_dir())?;
+        let mut file = File::create(HighScores::path())?;
+
+        for score in &self.scores{
+            writeln!(file, "{}\t{}\t{}\t{}\t{}\t{}\t{}", score.name, score.time, score.powerups,
+                score.peak_ash_rate, score.seed, score.date, score.difficulty.name())?;
+        }
+
+        Ok(())
+    }
+
+    ///Whether a run that lasted this long makes it onto the table
+    pub fn qualifies(&self, time: u64) -> bool{
+        self.scores.len() < MAX_SCORES || self.scores.iter().any(|score| time > score.time)
+    }
+
+    pub fn insert(&mut self, score: HighScore){
+        self.scores.push(score);
+        self.sort();
+    }
+
+    fn sort(&mut self){
+        self.scores.sort_by_key(|score| cmp::Reverse(score.time));
+        self.scores.truncate(MAX_SCORES);
+    }
+}
+
+///Reads one saved line, runs saved before there were difficulties were played on normal
+fn parse_score(line: &str) -> Option<HighScore>{
+    let parts: Vec<&str> = line.split('\t').collect();
+
+    let difficulty = match parts.len(){
+        6 => Difficulty::Normal,
+        7 => Difficulty::from_name(parts[6])?,
+        _ => return None,
+    };
+
+    Some(HighScore {
+        name: parts[0].to_string(),
+        time: parts[1].parse().ok()?,
+        powerups: parts[2].parse().ok()?,
+        peak_ash_rate: parts[3].parse().ok()?,
+        seed: parts[4].parse().ok()?,
+        date: parts[5].to_string(),
+        difficulty: difficulty
+    })
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn score(time: u64) -> HighScore{
+        HighScore {name: "Test".to_string(), time: time, powerups: 1, peak_ash_rate: 2, seed: 3, date: "2017-07-30".to_string(),
+            difficulty: Difficulty::Normal}
+    }
+
+    #[test]
+    fn keeps_the_longest_runs(){
+        let mut high_scores = HighScores {scores: Vec::new()};
+        for i in 0..MAX_SCORES as u64{
+            high_scores.insert(score(1000 * (i + 1)));
+        }
+
+        assert!(!high_scores.qualifies(500));
+        assert!(high_scores.qualifies(1500));
+
+        high_scores.insert(score(20000));
+        assert_eq!(high_scores.scores.len(), MAX_SCORES);
+        assert_eq!(high_scores.scores[0].time, 20000);
+        assert_eq!(high_scores.scores.last().unwrap().time, 2000);
+    }
+
+    #[test]
+    fn parses_saved_lines(){
+        assert_eq!(parse_score("Test\t1000\t1\t2\t3\t2017-07-30"), Some(score(1000)));
+        assert_eq!(parse_score("Test\tlong\t1\t2\t3\t2017-07-30"), None);
+
+        let hard = parse_score("Test\t1000\t1\t2\t3\t2017-07-30\thard").unwrap();
+        assert_eq!(hard.difficulty, Difficulty::Hard);
+        assert_eq!(parse_score("Test\t1000\t1\t2\t3\t2017-07-30\tsilly"), None);
+    }
+}
@@ -1,13 +1,20 @@
 extern crate cannon;
 
-use super::Game;
+#[cfg(windows)]
 use cannon::{Console};
 use cannon::input::*;
+#[cfg(unix)]
+use ansi;
+use bindings::KeyMap;
 use std::thread;
 use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
 
 pub struct InputSystem{
-    rx: Receiver<Key>
+    rx: Receiver<Key>,
+    pub keys: KeyMap,
+    #[cfg(unix)]
+    _raw_mode: ansi::RawMode
 }
 
 impl InputSystem{
@@ -18,18 +25,14 @@ impl InputSystem{
         }
     }
 
-    pub fn update(&self, game: &mut Game, key: Key){
-        match key{
-            Key::Left => game.player_pos.add_x(-1),
-            Key::Right => game.player_pos.add_x(1),
-            Key::Up => game.player_pos.add_y(-1),
-            Key::Down => game.player_pos.add_y(1),
-            _ => (),
-        }
+    ///Sleeps until a key is pressed or the timeout runs out
+    pub fn wait(&self, timeout: Duration) -> Option<Key>{
+        self.rx.recv_timeout(timeout).ok()
     }
 }
 
-pub fn init() -> InputSystem{
+#[cfg(windows)]
+pub fn init(keys: KeyMap) -> InputSystem{
     let (tx, rx) = channel();
 
     thread::spawn(move ||{
@@ -54,9 +57,30 @@ pub fn init() -> InputSystem{
         }
     });
 
-    InputSystem {rx: rx}
+    InputSystem {rx: rx, keys: keys}
 }
 
+#[cfg(unix)]
+pub fn init(keys: KeyMap) -> InputSystem{
+    let (tx, rx) = channel();
+    let raw_mode = ansi::RawMode::enable();
+
+    thread::spawn(move ||{
+        loop{
+            for code in ansi::read_key_codes(){
+                if let Some(key) = num_to_key(code){
+                    tx.send(key).unwrap_or_else(|err| {
+                        panic!("Input System Channel Error: {}", err);
+                    });
+                }
+            }
+        }
+    });
+
+    InputSystem {rx: rx, keys: keys, _raw_mode: raw_mode}
+}
+
+#[cfg(windows)]
 fn to_key(event: [u32;4]) -> Option<Key>{
     if event[0] == 1{
         return None;
@@ -64,3 +88,47 @@ fn to_key(event: [u32;4]) -> Option<Key>{
 
     num_to_key(event[2])
 }
+
+///The windows virtual key code for a key
+pub fn key_code(key: &Key) -> Option<u32>{
+    (0..256).find(|code| num_to_key(*code).as_ref() == Some(key))
+}
+
+///A readable name for a key, used in replay and config files
+pub fn key_name(key: &Key) -> String{
+    key_code(key).map(code_name).unwrap_or_else(|| "?".to_string())
+}
+
+pub fn code_name(code: u32) -> String{
+    match code{
+        0x08 => "Back".to_string(),
+        0x0D => "Enter".to_string(),
+        0x1B => "Escape".to_string(),
+        0x20 => "Space".to_string(),
+        0x25 => "Left".to_string(),
+        0x26 => "Up".to_string(),
+        0x27 => "Right".to_string(),
+        0x28 => "Down".to_string(),
+        0x30..=0x39 | 0x41..=0x5A => ((code as u8) as char).to_string(),
+        _ => format!("#{}", code),
+    }
+}
+
+///The opposite of `code_name`
+pub fn parse_code(name: &str) -> Option<u32>{
+    match name{
+        "Back" => Some(0x08),
+        "Enter" => Some(0x0D),
+        "Escape" => Some(0x1B),
+        "Space" => Some(0x20),
+        "Left" => Some(0x25),
+        "Up" => Some(0x26),
+        "Right" => Some(0x27),
+        "Down" => Some(0x28),
+        _ if name.starts_with('#') => name[1..].parse().ok(),
+        _ if name.len() == 1 && name.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            Some(name.to_ascii_uppercase().as_bytes()[0] as u32)
+        },
+        _ => None,
+    }
+}
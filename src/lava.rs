@@ -0,0 +1,96 @@
+use rand::Rng;
+use field::Field;
+use random::GameRng;
+use Powerup;
+
+///How often the lava moves
+pub const FLOW_MILLIS: u64 = 500;
+///Lava only spreads while it is younger than this many flows
+const SPREAD_AGE: u8 = 6;
+///After this many flows lava has cooled into rock
+pub const COOL_AGE: u8 = 30;
+
+///Percent chance each flow that hot lava runs into the cell below, and to each side
+const DOWN_CHANCE: u32 = 50;
+const SIDE_CHANCE: u32 = 15;
+
+///Moves the lava on by one flow. Hot lava runs downhill and outward,
+///swallowing any powerup it reaches, and old lava cools into rock. Lava
+///that spreads is as old as the lava it came from, so every drop only
+///runs so far. Returns whether anything changed.
+pub fn flow(field: &mut Field, rng: &mut GameRng) -> bool{
+    let mut spread = Vec::new();
+    let mut changed = false;
+
+    for x in 0..field.width(){
+        for y in 0..field.height(){
+            if field.get(x, y) != Some(Powerup::Destroyed){
+                continue;
+            }
+
+            let age = field.age(x, y).saturating_add(1);
+            if age >= COOL_AGE{
+                field.set(x, y, Some(Powerup::Rock));
+                changed = true;
+                continue;
+            }
+            field.set_age(x, y, age);
+
+            if age < SPREAD_AGE{
+                for &(dx, dy, chance) in [(0, 1, DOWN_CHANCE), (-1, 0, SIDE_CHANCE), (1, 0, SIDE_CHANCE)].iter(){
+                    if rng.gen_range(0, 100) < chance && can_flow_into(field, x + dx, y + dy){
+                        spread.push((x + dx, y + dy, age));
+                    }
+                }
+            }
+        }
+    }
+
+    for (x, y, age) in spread{
+        if can_flow_into(field, x, y){
+            field.set(x, y, Some(Powerup::Destroyed));
+            field.set_age(x, y, age);
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+///Lava runs over anything but other lava and rock
+fn can_flow_into(field: &Field, x: i16, y: i16) -> bool{
+    field.contains(x, y) && !matches!(field.get(x, y), Some(Powerup::Destroyed) | Some(Powerup::Rock))
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn runs_downhill_and_swallows_powerups(){
+        let mut field = Field::new(5, 10);
+        let mut rng = GameRng::new(8);
+        field.set(2, 0, Some(Powerup::Destroyed));
+        field.set(2, 1, Some(Powerup::Charge));
+        while field.get(2, 1) == Some(Powerup::Charge){
+            flow(&mut field, &mut rng);
+        }
+
+        assert_eq!(field.get(2, 1), Some(Powerup::Destroyed));
+        assert!(field.age(2, 1) > 0);
+    }
+
+    #[test]
+    fn cools_into_rock_and_stops(){
+        let mut field = Field::new(5, 5);
+        let mut rng = GameRng::new(9);
+        field.set(2, 2, Some(Powerup::Destroyed));
+        for _ in 0..COOL_AGE{
+            flow(&mut field, &mut rng);
+        }
+
+        assert_eq!(field.get(2, 2), Some(Powerup::Rock));
+        assert!(!(0..5).any(|x| (0..5).any(|y| field.get(x, y) == Some(Powerup::Destroyed))));
+        assert!(!flow(&mut field, &mut rng));
+    }
+}
@@ -2,61 +2,200 @@ extern crate cannon;
 extern crate rand;
 extern crate chroniker;
 
+#[cfg(unix)]
+pub mod ansi;
 pub mod input;
+pub mod field;
+pub mod spawn;
+pub mod lava;
+pub mod volcano;
+pub mod smoke;
+pub mod powerups;
+pub mod battery;
+pub mod effects;
 pub mod graphics;
 pub mod util;
+pub mod simulation;
+pub mod random;
+pub mod replay;
+pub mod highscore;
+pub mod save;
+pub mod bindings;
+pub mod config;
+pub mod scheduler;
+pub mod rules;
+pub mod bot;
+pub mod cli;
 
-use rand::{Rng, thread_rng, ThreadRng};
+#[cfg(windows)]
 use cannon::Console;
-use chroniker::Timer;
-use cannon::input::Key;
+#[cfg(unix)]
+use ansi::Console;
 use graphics::Graphics;
+use graphics::renderer::Renderer;
 use input::*;
 use graphics::consts as settings;
+use simulation::{Simulation, Event};
+use random::random_seed;
+use replay::{Replay, Playback};
+use highscore::{HighScore, HighScores, MAX_NAME_LENGTH};
+use bindings::{Action, Preset, KeyMap, ACTIONS};
+use config::Config;
+use scheduler::{Rates, Scheduler};
+use rules::{Mode, Rules, Setup};
+use bot::Bot;
+use cli::Options;
+use std::env;
+use std::io;
+use std::path::Path;
+use std::cmp;
+use std::time::Duration;
+use std::process;
 use util::*;
-
-pub type Field = [[Option<Powerup>; settings::WORLD_HEIGHT as usize]; settings::WORLD_WIDTH as usize];
+use field::Field;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Powerup{
-    Cleanup,
     Charge,
+    Cleanup,
     Reset,
-    Destroyed
+    ///Lava, which halves the charge of anyone walking into it and wears their battery
+    Destroyed,
+    ///Cooled lava. Safe to walk on and never picked up.
+    Rock,
+    Shield,
+    Magnet,
+    Freeze,
+    Teleport,
+    ///Makes the battery hold more
+    Upgrade,
+    ///A solar panel, charging the battery while the sky above it is clear.
+    ///Walked over like rock.
+    Panel
 }
 
 enum State{
     MainMenu,
     Quit,
-    Game
+    ///A new game with this setup
+    Game(Setup),
+    Continue,
+    Replay(Replay),
+    HighScores,
+    Paused(Session),
+    Resume(Session),
+    ///Reached from the pause menu with the game to go back to, or from the main menu without one
+    Options(Option<Session>)
 }
 
+///A game in progress, carried between the game, pause and options states
+struct Session{
+    sim: Simulation,
+    recording: Option<Replay>,
+    ///Plays instead of the keyboard when set
+    bot: Option<Bot>
+}
+
+///Headless games stop here even if nobody has lost, zen games never end
+const HEADLESS_LIMIT_MILLIS: u64 = 60 * 60 * 1000;
+
+///How long menus wait on a key before checking whether the console was resized
+const IDLE_MILLIS: u64 = 100;
+
+const PAUSE_ITEMS: [&str; 4] = ["Resume", "Restart", "Options", "Quit to Menu"];
+
 pub struct Game{
     player_pos: Position,
     items: Field
 }
 
 impl Game{
-    fn new() -> Game{
+    fn new(width: i16, height: i16) -> Game{
         Game {
-            player_pos: Position{x: 15, y: 15},
-            items:  [[None; settings::WORLD_HEIGHT as usize]; settings::WORLD_WIDTH as usize]
+            player_pos: Position{x: cmp::min(15, width - 1), y: cmp::min(15, height)},
+            items:  Field::new(width, height)
         }
     }
 }
 
-#[cfg(windows)]
 fn main() {
+    let options = cli::parse(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::USAGE);
+        process::exit(2);
+    });
+    if options.help{
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    let replay = options.replay.as_ref().map(|path| Replay::load(path).unwrap_or_else(|err| {
+        eprintln!("Could not load replay {}: {}", path.display(), err);
+        process::exit(1);
+    }));
+    let record_path = options.record.clone()
+        .unwrap_or_else(|| env::temp_dir().join("ash_dash_last.replay"));
+    let config_path = options.config.clone().unwrap_or_else(Config::default_path);
+    let mut config = Config::load(&config_path).unwrap_or_else(|err| {
+        eprintln!("Could not load config {}: {}", config_path.display(), err);
+        process::exit(1);
+    });
+
+    if options.headless{
+        let setup = setup(&options, (settings::FIELD_WIDTH, settings::FIELD_HEIGHT), &config.rules);
+        run_headless(&options, setup, replay, config.rates, &record_path);
+        return;
+    }
+
     let mut console = Console::new();//Passed
-    let mut graphics = Graphics::new();//Passed
-    let input = input::init();//Passed
-    let mut state = State::MainMenu;
+    let mut screen = Console::new();
+    screen.set_should_cls(true);
+    let mut graphics = Graphics::new(screen);//Passed
+    let mut input = input::init(config.keys.clone());//Passed
+    graphics.set_keys(&input.keys);
     console.set_should_cls(false);
+    let view = view_size(&console);
+    let mut setup = setup(&options, view, &config.rules);
+    graphics.set_view_size(view.0, view.1);
+    graphics.set_field_size(setup.width, setup.height);
+
+    let mut state = match replay{
+        Some(replay) => State::Replay(replay),
+        None if options.bot.is_some() => State::Game(setup),
+        None => State::MainMenu,
+    };
 
     'main: loop{
         state = match state{
-            State::Game => run_game(&mut console, &mut graphics, &input),
-            State::MainMenu => run_menu(&mut console, &mut graphics, &input),
+            State::Game(setup) => {
+                let sim = Simulation::new(options.seed.unwrap_or_else(random_seed), setup);
+                let recording = Some(Replay::new(sim.seed(), setup));
+                let bot = options.bot.map(|kind| Bot::new(kind, sim.seed()));
+                run_game(&mut console, &mut graphics, &input, config.rates,
+                    Session {sim: sim, recording: recording, bot: bot}, None, &record_path)
+            },
+            State::Resume(session) => run_game(&mut console, &mut graphics, &input, config.rates, session, None, &record_path),
+            State::Paused(session) => run_pause(&mut console, &mut graphics, &input, session, &record_path),
+            State::Options(session) => run_options(&mut console, &mut graphics, &mut input, session, &mut config, &config_path),
+            State::Continue => match save::load(){
+                Ok(sim) => run_game(&mut console, &mut graphics, &input, config.rates,
+                    Session {sim: sim, recording: None, bot: None}, None, &record_path),
+                Err(err) => {
+                    if err.kind() == io::ErrorKind::InvalidData{
+                        save::delete();
+                    }
+                    graphics.set_notice(&format!("Could not load the saved game: {}", err));
+                    State::MainMenu
+                },
+            },
+            State::Replay(replay) => run_game(&mut console, &mut graphics, &input, config.rates,
+                Session {sim: Simulation::new(replay.seed, replay.setup), recording: None, bot: None},
+                Some(replay.play()), &record_path),
+            State::MainMenu => {
+                let next = run_menu(&mut console, &mut graphics, &input, &mut setup, &config.rules);
+                graphics.set_notice("");
+                next
+            },
+            State::HighScores => run_high_scores(&mut console, &mut graphics, &input),
             State::Quit => break 'main,
         }
     }
@@ -65,206 +204,450 @@ fn main() {
 
 }
 
-fn run_menu(console: &mut Console, graphics: &mut Graphics, input: &InputSystem) -> State{
+///Plays a whole game as fast as possible without touching the console and
+///prints how it went. Bot games are recorded.
+fn run_headless(options: &Options, setup: Setup, replay: Option<Replay>, rates: Rates, record_path: &Path){
+    let (mut sim, mut playback) = match replay{
+        Some(ref replay) => (Simulation::new(replay.seed, replay.setup), Some(replay.play())),
+        None => (Simulation::new(options.seed.unwrap_or_else(random_seed), setup), None),
+    };
+    let mut bot = options.bot.map(|kind| Bot::new(kind, sim.seed()));
+    let mut recording = bot.as_ref().map(|_| Replay::new(sim.seed(), setup));
+
+    while !sim.is_over() && sim.elapsed() < HEADLESS_LIMIT_MILLIS{
+        if let Some(action) = bot.as_mut().and_then(|bot| bot.act(&sim)){
+            if let Some(ref mut recording) = recording{
+                recording.record(sim.elapsed(), action);
+            }
+            sim.apply_input(action);
+        }
+        step(&mut sim, playback.as_mut(), rates.tick_millis());
+    }
+
+    save_recording(&recording, record_path);
+    println!("Seed: {} Time: {} seconds Powerups: {} Peak ash rate: {}",
+        sim.seed(), sim.elapsed() / 1000, sim.powerups_collected, sim.peak_ash_rate);
+}
+
+///The setup new games start with. The field fills the view unless the
+///command line asks for a size, `custom` is used for the custom difficulty.
+fn setup(options: &Options, view: (i16, i16), custom: &Rules) -> Setup{
+    let mut setup = Setup::new(options.width.unwrap_or(cmp::max(view.0, settings::MIN_FIELD_WIDTH)),
+        options.height.unwrap_or(cmp::max(view.1, settings::MIN_FIELD_HEIGHT)));
+    setup.mode = options.mode;
+    setup.set_difficulty(options.difficulty, custom);
+    setup
+}
+
+///How much of the field fits on the console. The frame is drawn one cell
+///past the width, with the menu above and the status bar below the field.
+fn view_size(console: &Console) -> (i16, i16){
+    let size = console.get_console_size();
+    let width = size.width - 1;
+    let height = size.height - settings::FIELD_TOP - settings::STATUS_BAR_HEIGHT - 1;
+    (cmp::max(width, settings::MIN_VIEW_WIDTH), cmp::max(height, settings::MIN_VIEW_HEIGHT))
+}
+
+///Left and right pick the difficulty of the next game
+fn run_menu<R: Renderer>(console: &mut Console, graphics: &mut Graphics<R>, input: &InputSystem,
+    setup: &mut Setup, custom: &Rules) -> State{
     let mut size = console.get_console_size();
-    graphics.draw_main_menu();
+    graphics.draw_main_menu(save::exists(), setup.difficulty);
     loop{
-        if let Some(k) = input.poll(){
-            match k {
-                Key::Escape => return State::Quit,
-                Key::Enter => return State::Game,
-                _ => (),
+        graphics.flush();
+        if let Some(k) = input.wait(Duration::from_millis(IDLE_MILLIS)){
+            let keys = &input.keys;
+
+            if keys.is(Action::Quit, &k){
+                return State::Quit;
+            }else if keys.is(Action::Confirm, &k){
+                return State::Game(*setup);
+            }else if keys.is(Action::HighScores, &k){
+                return State::HighScores;
+            }else if keys.is(Action::Continue, &k) && save::exists(){
+                return State::Continue;
+            }else if keys.is(Action::Options, &k){
+                return State::Options(None);
+            }else if keys.is(Action::MoveLeft, &k) || keys.is(Action::MoveRight, &k){
+                let step = if keys.is(Action::MoveLeft, &k) { -1 }else{ 1 };
+                let difficulty = setup.difficulty.cycle(step);
+                setup.set_difficulty(difficulty, custom);
+                graphics.draw_main_menu(save::exists(), setup.difficulty);
             }
         }
 
         if size != console.get_console_size(){
             size = console.get_console_size();
-            graphics.draw_main_menu();
+            graphics.draw_main_menu(save::exists(), setup.difficulty);
         }
     }
 }
 
-fn run_game(console: &mut Console, graphics: &mut Graphics, input: &InputSystem) -> State{
-    let mut size = console.get_console_size();//Created
-    let mut game = Game::new();//Created
-    let mut charge = 10;//Created
-    let mut timer: Timer = Timer::new();//Created
-    let start_time = chroniker::current_time_millis();//Created
-    let mut rng = thread_rng();//Created
-    let mut ash_rate = 0;
-    let mut powerups_collected = 0;
-
-    for _ in 0..25{
-        spawn_charge(&mut game.items, &mut rng);
-    }
+fn run_high_scores<R: Renderer>(console: &mut Console, graphics: &mut Graphics<R>, input: &InputSystem) -> State{
+    let mut size = console.get_console_size();
+    let high_scores = HighScores::load();
+    graphics.draw_high_scores(&high_scores.scores);
 
-    for _ in 0..10{
-        spawn_cleanup(&mut game.items, &mut rng);
-    }
+    loop{
+        graphics.flush();
+        if let Some(k) = input.wait(Duration::from_millis(IDLE_MILLIS)){
+            if input.keys.is(Action::Quit, &k) || input.keys.is(Action::Confirm, &k){
+                return State::MainMenu;
+            }
+        }
 
-    for _ in 0..10{
-        spawn_reset(&mut game.items, &mut rng);
+        if size != console.get_console_size(){
+            size = console.get_console_size();
+            graphics.draw_high_scores(&high_scores.scores);
+        }
     }
+}
 
-    game.items[3][3] = Some(Powerup::Charge);
-    graphics.redraw_background();
-    graphics.draw_player(&game.player_pos);
-    graphics.draw_powerups(&game.items);
+///Plays a game, either from the keyboard or fed by a replay's playback.
+///Actions are only recorded when the session has a recording. The
+///simulation steps at the tick rate and the screen is flushed at the frame
+///rate, in between the loop sleeps on input.
+fn run_game<R: Renderer>(console: &mut Console, graphics: &mut Graphics<R>, input: &InputSystem, rates: Rates,
+    mut session: Session, mut playback: Option<Playback>, record_path: &Path) -> State{
+    let mut size = console.get_console_size();//Created
+    let mut scheduler = Scheduler::new(rates, chroniker::current_time_millis());
+
+    graphics.set_field_size(session.sim.game.items.width(), session.sim.game.items.height());
+    draw_game(graphics, &session.sim);
 
     loop {
-        if let Some(k) = input.poll(){
-            if k == Key::Escape{
-                return State::Quit;
-            }else{
-                let prev_player_pos = game.player_pos.clone();
-                input.update(&mut game, k);
-                if prev_player_pos != game.player_pos{
-                    graphics.move_player(&game.player_pos, &prev_player_pos);
-                }
-                if let Some(powerup) = game.items[game.player_pos.x as usize][(game.player_pos.y - 1)as usize]{
-                    powerups_collected += 1;
-                    match powerup{
-                        Powerup::Charge => {
-                            charge += 5;
-                            spawn_charge(&mut game.items, &mut rng);
-                            ()},
-                        Powerup::Destroyed => charge /= 2,
-                        Powerup::Reset => {ash_rate = 0; spawn_reset(&mut game.items, &mut rng); ()},
-                        Powerup::Cleanup => {
-                            cleanup(&mut game.items, &mut rng, graphics);
-                            ash_rate *= 2;
-                            ()
-                            }
-                    }
+        let mut events = Vec::new();
+
+        match input.wait(scheduler.timeout(chroniker::current_time_millis())){
+            Some(ref k) if playback.is_some() && input.keys.is(Action::Quit, k) => return State::MainMenu,
+            Some(_) if playback.is_some() => (),
+            Some(ref k) if input.keys.is(Action::Pause, k) => return State::Paused(session),
+            Some(ref k) => if let Some(action) = input.keys.play_action(k).filter(|_| session.bot.is_none()){
+                if let Some(ref mut recording) = session.recording{
+                    recording.record(session.sim.elapsed(), action);
                 }
-            }
-            game.items[game.player_pos.x as usize][(game.player_pos.y - 1) as usize] = None;
+                events.extend(session.sim.apply_input(action));
+            },
+            None => (),
         }
 
-        if charge > 70{
-            charge = 70;
-        }
-
-        if charge < 1{
-            graphics.set_status(&format!("GAME OVER! Time: {} seconds",
-                (chroniker::current_time_millis() - start_time) / 1000));
-            let end_time = chroniker::current_time_millis();
-            size = console.get_console_size();
-            console.set_console_size(size.width, size.height -1);//Updates buffer?
-            graphics.redraw_background();
-            graphics.draw_player(&game.player_pos);
-            graphics.draw_powerups(&game.items);
-            graphics.draw_menu_ui(end_time - start_time, ash_rate, powerups_collected);
-            graphics.draw_end_game(end_time - start_time);
-
-            loop{
-                if let Some(k) = input.poll(){
-                    if k == Key::Escape || k == Key::Q{
-                        return State::MainMenu;
-                    }else if k == Key::R{
-                        return State::Game;
-                    }
-                }
-
-                if size != console.get_console_size(){
-                    size = console.get_console_size();
-                    console.set_console_size(size.width, size.height -1);//Updates buffer?
-                    graphics.redraw_background();
-                    graphics.draw_player(&game.player_pos);
-                    graphics.draw_powerups(&game.items);
-                    graphics.draw_menu_ui(end_time - start_time, ash_rate, powerups_collected);
-                    graphics.draw_end_game(end_time - start_time);
+        let now = chroniker::current_time_millis();
+        for _ in 0..scheduler.ticks(now){
+            let sim = &session.sim;
+            if let Some(action) = session.bot.as_mut().and_then(|bot| bot.act(sim)){
+                if let Some(ref mut recording) = session.recording{
+                    recording.record(session.sim.elapsed(), action);
                 }
+                events.extend(session.sim.apply_input(action));
             }
-            break;
+            events.extend(step(&mut session.sim, playback.as_mut(), scheduler.tick_millis()));
         }
 
-        if timer.elapsed_millis() > 1000{
-            charge -= 2;
-            graphics.draw_powerups(&game.items);
-            graphics.update_charge(charge);
-            graphics.draw_menu_ui(chroniker::current_time_millis() - start_time, ash_rate, powerups_collected);
-            timer.reset();
-            rng = thread_rng();
-            ash_rate += 1;
-            for _ in 0..(ash_rate){
-                drop_ash(&mut game.items, &mut rng);
+        let sim = &session.sim;
+        for event in events{
+            match event{
+                Event::PlayerMoved{from} => graphics.move_player(&sim.game.player_pos, &from, &sim.game.items),
+                Event::Cleared{x, y} => graphics.clear_cell(x, y),
+                Event::Tick => {
+                    graphics.set_volcano(&sim.volcano);
+                    graphics.set_falling(&sim.falling);
+                    graphics.set_smoke(&sim.smoke);
+                    graphics.draw_ground(&sim.game.player_pos);
+                    graphics.draw_player(&sim.game.player_pos);
+                    graphics.draw_powerups(&sim.game.items);
+                    graphics.update_charge(&sim.battery);
+                    graphics.draw_menu_ui(sim.elapsed(), sim.ash_rate, sim.powerups_collected);
+                    graphics.draw_minimap(&sim.game.items, &sim.game.player_pos);
+                    graphics.draw_effects(&sim.effects);
+                },
+                Event::FieldChanged => {
+                    graphics.draw_powerups(&sim.game.items);
+                    graphics.draw_minimap(&sim.game.items, &sim.game.player_pos);
+                },
+                Event::EruptionWarning{seconds} => graphics.set_status(&eruption_warning(seconds)),
+                Event::AshHit => graphics.set_status("Falling ash hit you! Watch for the shadows"),
+                Event::ShieldUsed => {
+                    graphics.set_status("Your shield took the hit");
+                    graphics.draw_effects(&sim.effects);
+                },
+                Event::EffectEnded(effect) => {
+                    graphics.set_status(&format!("{} wore off", effect.label()));
+                    graphics.draw_effects(&sim.effects);
+                },
+                Event::GameOver => {
+                    if playback.is_none(){
+                        save_recording(&session.recording, record_path);
+                        save::delete();
+                    }
+                    let submit_score = playback.is_none() && session.bot.is_none() && sim.setup().mode == Mode::Classic;
+                    return run_game_over(console, graphics, input, sim, submit_score);
+                },
+                Event::Collected(_) => graphics.draw_effects(&sim.effects),
+                Event::PanelPlaced => {
+                    graphics.set_status(&format!("Solar panel put down, {} left", sim.panels));
+                    graphics.draw_player(&sim.game.player_pos);
+                },
+                Event::NoPanels => graphics.set_status("You have no solar panels left to put down"),
             }
         }
 
         if console.get_console_size() != size{
             size = console.get_console_size();
             console.set_console_size(size.width, size.height -1);//Updates buffer?
-            graphics.redraw_background();
-            graphics.draw_player(&game.player_pos);
-            graphics.draw_powerups(&game.items);
+            let view = view_size(console);
+            graphics.set_view_size(view.0, view.1);
+            graphics.set_field_size(sim.game.items.width(), sim.game.items.height());
+            draw_game(graphics, sim);
+        }
+
+        if scheduler.frame_due(now){
+            graphics.flush();
         }
     }
-    State::MainMenu
 }
 
-fn drop_ash(field: &mut Field, rng: &mut ThreadRng){
-    let x =  rng.gen_range(0, settings::WORLD_WIDTH) as usize;
-    let y =  rng.gen_range(0, settings::WORLD_HEIGHT - 6) as usize;
+///Moves the simulation on by `millis`, feeding in replayed actions at the
+///times they were recorded
+fn step(sim: &mut Simulation, playback: Option<&mut Playback>, millis: u64) -> Vec<Event>{
+    let playback = match playback{
+        Some(playback) => playback,
+        None => return sim.tick(millis),
+    };
+    let end = sim.elapsed() + millis;
+    let mut events = Vec::new();
 
-    if field[x][y] == Some(Powerup::Destroyed){
-        drop_ash(field, rng);
-    }else{
-        field[x][y] = Some(Powerup::Destroyed);
+    loop{
+        while let Some(action) = playback.poll(sim.elapsed()){
+            events.extend(sim.apply_input(action));
+        }
+
+        if sim.elapsed() >= end || sim.is_over(){
+            return events;
+        }
+
+        let next = playback.next_time().map_or(end, |time| cmp::min(time, end));
+        events.extend(sim.tick(next - sim.elapsed()));
     }
 }
 
-fn spawn_cleanup(field: &mut Field, rng: &mut ThreadRng){
-    let x =  rng.gen_range(0, settings::WORLD_WIDTH) as usize;
-    let y =  rng.gen_range(0, settings::WORLD_HEIGHT - 6) as usize;
+///The game is frozen while this runs, so paused time never counts towards
+///the survival time
+fn run_pause<R: Renderer>(console: &mut Console, graphics: &mut Graphics<R>, input: &InputSystem,
+    session: Session, record_path: &Path) -> State{
+    let mut size = console.get_console_size();
+    let mut selected = 0;
+    graphics.set_status("Paused");
+    draw_pause(graphics, &session.sim, selected);
 
-    if field[x][y] == None{
-        field[x][y] = Some(Powerup::Cleanup);
-    }else{
-        spawn_cleanup(field, rng);
+    loop{
+        graphics.flush();
+        if let Some(k) = input.wait(Duration::from_millis(IDLE_MILLIS)){
+            let keys = &input.keys;
+            let resume = keys.is(Action::Pause, &k);
+
+            if keys.is(Action::MoveUp, &k){
+                selected = (selected + PAUSE_ITEMS.len() - 1) % PAUSE_ITEMS.len();
+            }else if keys.is(Action::MoveDown, &k){
+                selected = (selected + 1) % PAUSE_ITEMS.len();
+            }else if resume || keys.is(Action::Confirm, &k){
+                graphics.set_status("");
+                return match if resume { 0 } else { selected }{
+                    0 => State::Resume(session),
+                    1 => State::Game(session.sim.setup()),
+                    2 => State::Options(Some(session)),
+                    _ => {
+                        save_recording(&session.recording, record_path);
+                        if let Err(err) = save::save(&session.sim){
+                            graphics.set_notice(&format!("Could not save the game: {}", err));
+                        }
+                        State::MainMenu
+                    },
+                };
+            }
+            graphics.draw_menu_box("Paused", &PAUSE_ITEMS, selected);
+        }
+
+        if size != console.get_console_size(){
+            size = console.get_console_size();
+            draw_pause(graphics, &session.sim, selected);
+        }
     }
 }
 
-fn cleanup(field: &mut Field, rng: &mut ThreadRng, graphics: &mut graphics::Graphics){
-    spawn_cleanup(field, rng);
+fn draw_pause<R: Renderer>(graphics: &mut Graphics<R>, sim: &Simulation, selected: usize){
+    draw_game(graphics, sim);
+    graphics.draw_menu_box("Paused", &PAUSE_ITEMS, selected);
+}
 
-    for x in 0..settings::WORLD_WIDTH{
-        for y in 0..settings::WORLD_HEIGHT{
-            if let Some(powerup) = field[x as usize][y as usize]{
-                if powerup == Powerup::Destroyed{
-                    if rng.gen_range(0,100) <= 66{
-                        field[x as usize][y as usize] = None;
-                        graphics.remove_ash(x, y);
+///Shows every action with its keys. Enter rebinds the selected action to
+///the next key pressed and 1, 2 and 3 switch to the arrows, WASD and hjkl
+///presets. The arrow keys and Escape always work here, so a bad binding
+///can never lock the player out.
+fn run_options<R: Renderer>(console: &mut Console, graphics: &mut Graphics<R>, input: &mut InputSystem,
+    session: Option<Session>, config: &mut Config, config_path: &Path) -> State{
+    let mut size = console.get_console_size();
+    let mut selected = 0;
+    let mut waiting = false;
+    graphics.draw_options(&input.keys, selected, waiting);
+
+    loop{
+        graphics.flush();
+        if let Some(k) = input.wait(Duration::from_millis(IDLE_MILLIS)){
+            let code = key_code(&k);
+
+            if waiting{
+                if let Some(code) = code{
+                    if code != 0x1B{
+                        input.keys.bind(ACTIONS[selected], vec![code]);
                     }
                 }
+                waiting = false;
+            }else if code == Some(0x1B){
+                config.keys = input.keys.clone();
+                if let Err(err) = config.save(config_path){
+                    graphics.set_status(&format!("Could not save the config: {}", err));
+                }
+                graphics.set_keys(&input.keys);
+
+                return match session{
+                    Some(session) => State::Paused(session),
+                    None => State::MainMenu,
+                };
+            }else if code == Some(0x26) || input.keys.is(Action::MoveUp, &k){
+                selected = (selected + ACTIONS.len() - 1) % ACTIONS.len();
+            }else if code == Some(0x28) || input.keys.is(Action::MoveDown, &k){
+                selected = (selected + 1) % ACTIONS.len();
+            }else if code == Some(0x0D) || input.keys.is(Action::Confirm, &k){
+                waiting = true;
+            }else if let Some(preset) = code.and_then(preset_for){
+                input.keys = KeyMap::preset(preset);
             }
+            graphics.draw_options(&input.keys, selected, waiting);
+        }
+
+        if size != console.get_console_size(){
+            size = console.get_console_size();
+            graphics.draw_options(&input.keys, selected, waiting);
         }
     }
 }
 
-fn spawn_charge(field: &mut Field, rng: &mut ThreadRng){
-    let x =  rng.gen_range(0, settings::WORLD_WIDTH) as usize;
-    let y =  rng.gen_range(0, settings::WORLD_HEIGHT - 6) as usize;
+fn preset_for(code: u32) -> Option<Preset>{
+    match code{
+        0x31 => Some(Preset::Arrows),
+        0x32 => Some(Preset::Wasd),
+        0x33 => Some(Preset::Hjkl),
+        _ => None,
+    }
+}
 
-    if field[x][y] == None{
-        field[x][y] = Some(Powerup::Charge);
-    }else{
-        spawn_charge(field, rng);
+fn eruption_warning(seconds: u32) -> String{
+    match seconds{
+        0 => "Kuklonfusta erupts! Stay clear of the crater".to_string(),
+        _ => format!("Kuklonfusta is rumbling, a big eruption in {} seconds!", seconds),
     }
 }
 
-fn spawn_reset(field: &mut Field, rng: &mut ThreadRng){
-    let x =  rng.gen_range(0, settings::WORLD_WIDTH) as usize;
-    let y =  rng.gen_range(0, settings::WORLD_HEIGHT - 6) as usize;
+fn save_recording(recording: &Option<Replay>, path: &Path){
+    if let Some(ref recording) = *recording{
+        recording.save(path).ok();
+    }
+}
+
+fn draw_game<R: Renderer>(graphics: &mut Graphics<R>, sim: &Simulation){
+    graphics.redraw_background();
+    graphics.set_volcano(&sim.volcano);
+    graphics.set_falling(&sim.falling);
+    graphics.set_smoke(&sim.smoke);
+    graphics.draw_ground(&sim.game.player_pos);
+    graphics.draw_player(&sim.game.player_pos);
+    graphics.draw_powerups(&sim.game.items);
+    graphics.update_charge(&sim.battery);
+    graphics.draw_menu_ui(sim.elapsed(), sim.ash_rate, sim.powerups_collected);
+    graphics.draw_minimap(&sim.game.items, &sim.game.player_pos);
+    graphics.draw_effects(&sim.effects);
+}
 
-    if field[x][y] == None{
-        field[x][y] = Some(Powerup::Reset);
-    }else{
-        spawn_charge(field, rng);
+fn run_game_over<R: Renderer>(console: &mut Console, graphics: &mut Graphics<R>, input: &InputSystem,
+    sim: &Simulation, submit_score: bool) -> State{
+    graphics.set_status(&format!("GAME OVER! Time: {} seconds Seed: {}", sim.elapsed() / 1000, sim.seed()));
+    let mut size = console.get_console_size();
+    console.set_console_size(size.width, size.height -1);//Updates buffer?
+    draw_game_over(graphics, sim);
+
+    let mut high_scores = HighScores::load();
+    if submit_score && high_scores.qualifies(sim.elapsed()){
+        if let Some(name) = run_name_entry(console, graphics, input, sim){
+            high_scores.insert(HighScore {
+                name: name,
+                time: sim.elapsed(),
+                powerups: sim.powerups_collected,
+                peak_ash_rate: sim.peak_ash_rate,
+                seed: sim.seed(),
+                date: util::today(),
+                difficulty: sim.setup().difficulty
+            });
+
+            if let Err(err) = high_scores.save(){
+                graphics.set_status(&format!("Could not save high scores: {}", err));
+            }
+        }
+        size = console.get_console_size();
+        draw_game_over(graphics, sim);
+    }
+
+    loop{
+        graphics.flush();
+        if let Some(k) = input.wait(Duration::from_millis(IDLE_MILLIS)){
+            if input.keys.is(Action::Quit, &k){
+                return State::MainMenu;
+            }else if input.keys.is(Action::Restart, &k){
+                return State::Game(sim.setup());
+            }
+        }
+
+        if size != console.get_console_size(){
+            size = console.get_console_size();
+            console.set_console_size(size.width, size.height -1);//Updates buffer?
+            draw_game_over(graphics, sim);
+        }
     }
 }
 
-#[cfg(not(windows))]
-fn main() {
-    println!("This game only works on Windows! Sorry! 5s Please!");
+///Asks for a name for the high score table. Escape skips it.
+fn run_name_entry<R: Renderer>(console: &mut Console, graphics: &mut Graphics<R>, input: &InputSystem,
+    sim: &Simulation) -> Option<String>{
+    let mut size = console.get_console_size();
+    let mut name = String::new();
+    graphics.draw_name_entry(&name);
+
+    loop{
+        graphics.flush();
+        if let Some(k) = input.wait(Duration::from_millis(IDLE_MILLIS)){
+            match key_code(&k){
+                Some(0x0D) if !name.trim().is_empty() => return Some(name.trim().to_string()),
+                Some(0x1B) => return None,
+                Some(0x08) => {name.pop();},
+                Some(code @ 0x20) | Some(code @ 0x30..=0x39) | Some(code @ 0x41..=0x5A) if name.len() < MAX_NAME_LENGTH => {
+                    name.push((code as u8) as char);
+                },
+                _ => (),
+            }
+            graphics.draw_name_entry(&name);
+        }
+
+        if size != console.get_console_size(){
+            size = console.get_console_size();
+            draw_game_over(graphics, sim);
+            graphics.draw_name_entry(&name);
+        }
+    }
+}
+
+fn draw_game_over<R: Renderer>(graphics: &mut Graphics<R>, sim: &Simulation){
+    graphics.redraw_background();
+    graphics.draw_player(&sim.game.player_pos);
+    graphics.draw_powerups(&sim.game.items);
+    graphics.draw_menu_ui(sim.elapsed(), sim.ash_rate, sim.powerups_collected);
+    graphics.draw_end_game(sim.elapsed(), sim.seed(), sim.setup().difficulty);
 }
@@ -0,0 +1,172 @@
+use cannon::color;
+use simulation::{Event, Simulation};
+use spawn::SpawnTable;
+use effects::Effect;
+use Powerup;
+
+///Everything that makes a kind of powerup what it is. To add one, give
+///`Powerup` a variant and add its kind to `KINDS` in the same place.
+pub struct Kind{
+    pub powerup: Powerup,
+    ///Drawn on the field
+    pub glyph: &'static str,
+    pub foreground: u16,
+    pub background: u16,
+    ///Stands for the powerup in save files
+    pub symbol: char,
+    ///What the main menu says it does, left off the list when empty
+    pub description: &'static str,
+    ///How likely it is to be the bonus powerup that turns up every few seconds
+    pub spawn_weight: u32,
+    ///Seconds it lies on the field before it fades away, `None` to stay forever
+    pub lifetime: Option<u8>,
+    ///Whether walking onto it uses it up, rock is only walked over
+    pub picked_up: bool,
+    ///What walking onto it does to the game
+    pub effect: fn(&mut Simulation, &mut Vec<Event>)
+}
+
+///Every kind, in the same order as `Powerup`
+pub static KINDS: [Kind; 11] = [
+    Kind {
+        powerup: Powerup::Charge, glyph: "₽", foreground: color::YELLOW, background: color::BROWN, symbol: 'P',
+        description: "₽: Increases the power in your battery (Bottom of the Screen)",
+        spawn_weight: 0, lifetime: None, picked_up: true, effect: charge
+    },
+    Kind {
+        powerup: Powerup::Cleanup, glyph: "C", foreground: color::YELLOW, background: color::BROWN, symbol: 'C',
+        description: "C: Removes 66% of the magma, but doubles the rate the volcano spews lava",
+        spawn_weight: 0, lifetime: None, picked_up: true, effect: clean_up
+    },
+    Kind {
+        powerup: Powerup::Reset, glyph: "R", foreground: color::YELLOW, background: color::BROWN, symbol: 'R',
+        description: "R: Calms the volcano",
+        spawn_weight: 0, lifetime: None, picked_up: true, effect: reset
+    },
+    Kind {
+        powerup: Powerup::Destroyed, glyph: " ", foreground: color::DARK_RED, background: color::DARK_RED, symbol: '#',
+        description: "",
+        spawn_weight: 0, lifetime: None, picked_up: true, effect: burn
+    },
+    Kind {
+        powerup: Powerup::Rock, glyph: "▓", foreground: color::DARK_GRAY, background: color::LIGHT_GRAY, symbol: 'o',
+        description: "",
+        spawn_weight: 0, lifetime: None, picked_up: false, effect: nothing
+    },
+    Kind {
+        powerup: Powerup::Shield, glyph: "S", foreground: color::CYAN, background: color::BROWN, symbol: 'S',
+        description: "S: A shield that takes the next magma hit for you, stacks up to 3",
+        spawn_weight: 3, lifetime: Some(30), picked_up: true, effect: shield
+    },
+    Kind {
+        powerup: Powerup::Magnet, glyph: "M", foreground: color::MAGENTA, background: color::BROWN, symbol: 'M',
+        description: "M: Pulls the ₽ around you closer for a few seconds",
+        spawn_weight: 3, lifetime: Some(30), picked_up: true, effect: magnet
+    },
+    Kind {
+        powerup: Powerup::Freeze, glyph: "F", foreground: color::WHITE, background: color::BROWN, symbol: 'F',
+        description: "F: No ash falls for 10 seconds",
+        spawn_weight: 1, lifetime: Some(20), picked_up: true, effect: freeze
+    },
+    Kind {
+        powerup: Powerup::Teleport, glyph: "T", foreground: color::GREEN, background: color::BROWN, symbol: 'T',
+        description: "T: Takes you to an empty spot far away",
+        spawn_weight: 2, lifetime: Some(30), picked_up: true, effect: teleport
+    },
+    Kind {
+        powerup: Powerup::Upgrade, glyph: "U", foreground: color::DARK_CYAN, background: color::BROWN, symbol: 'U',
+        description: "U: Makes your battery hold more power",
+        spawn_weight: 1, lifetime: Some(20), picked_up: true, effect: upgrade
+    },
+    Kind {
+        powerup: Powerup::Panel, glyph: "≡", foreground: color::CYAN, background: color::DARK_BLUE, symbol: '=',
+        description: "≡: A solar panel, it charges your battery while no smoke hangs over it",
+        spawn_weight: 1, lifetime: None, picked_up: false, effect: nothing
+    },
+];
+
+pub fn kind(powerup: Powerup) -> &'static Kind{
+    &KINDS[powerup as usize]
+}
+
+pub fn from_symbol(symbol: char) -> Option<Powerup>{
+    KINDS.iter().find(|kind| kind.symbol == symbol).map(|kind| kind.powerup)
+}
+
+///The bonus powerups, by their spawn weights
+pub fn bonus_table() -> SpawnTable{
+    let entries: Vec<(Powerup, u32)> = KINDS.iter()
+        .filter(|kind| kind.spawn_weight > 0)
+        .map(|kind| (kind.powerup, kind.spawn_weight))
+        .collect();
+    SpawnTable::new(&entries)
+}
+
+fn charge(sim: &mut Simulation, _events: &mut Vec<Event>){
+    sim.battery.add(sim.setup().rules.charge_bonus);
+    sim.spawn(Powerup::Charge);
+}
+
+fn clean_up(sim: &mut Simulation, events: &mut Vec<Event>){
+    sim.spawn(Powerup::Cleanup);
+    sim.clear_ash(events);
+    sim.ash_rate = sim.ash_rate.saturating_mul(2);
+    sim.peak_ash_rate = sim.peak_ash_rate.max(sim.ash_rate);
+}
+
+fn reset(sim: &mut Simulation, _events: &mut Vec<Event>){
+    sim.ash_rate = 0;
+    sim.volcano.calm();
+    sim.spawn(Powerup::Reset);
+}
+
+fn burn(sim: &mut Simulation, events: &mut Vec<Event>){
+    sim.hurt(events);
+}
+
+fn nothing(_sim: &mut Simulation, _events: &mut Vec<Event>){}
+
+fn shield(sim: &mut Simulation, _events: &mut Vec<Event>){
+    sim.effects.add(Effect::Shield);
+}
+
+fn magnet(sim: &mut Simulation, events: &mut Vec<Event>){
+    sim.effects.add(Effect::Magnet);
+    sim.pull_charges(events);
+}
+
+fn freeze(sim: &mut Simulation, _events: &mut Vec<Event>){
+    sim.effects.add(Effect::Frozen);
+}
+
+fn teleport(sim: &mut Simulation, events: &mut Vec<Event>){
+    sim.teleport(events);
+}
+
+fn upgrade(sim: &mut Simulation, _events: &mut Vec<Event>){
+    sim.battery.upgrade();
+}
+
+#[cfg(test)]
+mod tests{
+    use rules::Setup;
+    use super::*;
+
+    #[test]
+    fn kinds_are_in_powerup_order(){
+        for (i, kind) in KINDS.iter().enumerate(){
+            assert_eq!(kind.powerup as usize, i);
+            assert_eq!(from_symbol(kind.symbol), Some(kind.powerup));
+        }
+    }
+
+    #[test]
+    fn cleanup_doubles_the_ash_rate_up_to_its_limit(){
+        let mut sim = Simulation::new(1, Setup::new(80, 20));
+        sim.ash_rate = 40000;
+        clean_up(&mut sim, &mut Vec::new());
+
+        assert_eq!(sim.ash_rate, u16::MAX);
+        assert_eq!(sim.peak_ash_rate, u16::MAX);
+    }
+}
@@ -0,0 +1,57 @@
+use rand::{Rng, SeedableRng};
+use chroniker;
+
+///A small xorshift64* generator. Unlike `ThreadRng` it can be seeded, so a
+///seed always plays out the same game.
+#[derive(Clone, Debug)]
+pub struct GameRng{
+    state: u64
+}
+
+impl GameRng{
+    pub fn new(seed: u64) -> GameRng{
+        SeedableRng::from_seed(seed)
+    }
+
+    ///The internal state, which is enough to resume the exact sequence
+    pub fn state(&self) -> u64{
+        self.state
+    }
+
+    pub fn from_state(state: u64) -> GameRng{
+        GameRng {state: if state == 0 { 1 } else { state }}
+    }
+}
+
+impl Rng for GameRng{
+    fn next_u32(&mut self) -> u32{
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64{
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl SeedableRng<u64> for GameRng{
+    fn reseed(&mut self, seed: u64){
+        *self = SeedableRng::from_seed(seed);
+    }
+
+    ///Runs the seed through splitmix64 so that small seeds still start well mixed
+    fn from_seed(seed: u64) -> GameRng{
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        GameRng::from_state(z ^ (z >> 31))
+    }
+}
+
+///A seed for when the player did not ask for one. Kept short enough to
+///read off the game over screen and type back in.
+pub fn random_seed() -> u64{
+    (chroniker::current_time_millis() * 7919) % 1_000_000
+}
@@ -0,0 +1,174 @@
+use bindings::Action;
+use graphics::consts;
+use rules::{Difficulty, Mode, Rules, Setup};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+const HEADER: &str = "ashdash-replay 13";
+
+///The seed and setup of a game and every action it received, stamped with the
+///simulation time in milliseconds. Feeding the actions back at the same
+///times plays out the exact same game, whatever keys they are bound to.
+pub struct Replay{
+    pub seed: u64,
+    pub setup: Setup,
+    inputs: Vec<(u64, Action)>
+}
+
+impl Replay{
+    pub fn new(seed: u64, setup: Setup) -> Replay{
+        Replay {seed: seed, setup: setup, inputs: Vec::new()}
+    }
+
+    pub fn record(&mut self, time: u64, action: Action){
+        self.inputs.push((time, action));
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()>{
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", HEADER)?;
+        writeln!(file, "seed {}", self.seed)?;
+        writeln!(file, "size {} {}", self.setup.width, self.setup.height)?;
+        writeln!(file, "rules {} {} {}", self.setup.difficulty.name(), self.setup.mode.name(), self.setup.rules.describe())?;
+
+        for &(time, action) in &self.inputs{
+            writeln!(file, "{} {}", time, action.name())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Replay>{
+        let reader = BufReader::new(File::open(path)?);
+        let mut lines = reader.lines();
+
+        if lines.next().unwrap_or(Ok(String::new()))? != HEADER{
+            return Err(invalid("not a replay file"));
+        }
+
+        let seed = lines.next().unwrap_or(Ok(String::new()))?;
+        let seed = match seed.split_whitespace().collect::<Vec<_>>()[..]{
+            ["seed", seed] => seed.parse().map_err(|_| invalid("bad seed"))?,
+            _ => return Err(invalid("missing seed")),
+        };
+
+        let size = lines.next().unwrap_or(Ok(String::new()))?;
+        let mut setup = match size.split_whitespace().collect::<Vec<_>>()[..]{
+            ["size", width, height] => match (width.parse(), height.parse()){
+                (Ok(width), Ok(height)) if width > consts::MAX_FIELD_WIDTH || height > consts::MAX_FIELD_HEIGHT =>
+                    return Err(invalid("the field is too big")),
+                (Ok(width), Ok(height)) if width > 0 && height > 0 => Setup::new(width, height),
+                _ => return Err(invalid("bad size")),
+            },
+            _ => return Err(invalid("missing size")),
+        };
+
+        let rules = lines.next().unwrap_or(Ok(String::new()))?;
+        match rules.split_whitespace().collect::<Vec<_>>()[..]{
+            ["rules", difficulty, mode, ref values @ ..] => {
+                setup.difficulty = Difficulty::from_name(difficulty).ok_or_else(|| invalid("unknown difficulty"))?;
+                setup.mode = Mode::from_name(mode).ok_or_else(|| invalid("unknown mode"))?;
+                setup.rules = Rules::parse(&values.join(" ")).map_err(|err| invalid(&err))?;
+            },
+            _ => return Err(invalid("missing rules")),
+        }
+        let mut replay = Replay::new(seed, setup);
+
+        for line in lines{
+            let line = line?;
+            let mut parts = line.split_whitespace();
+
+            let time = parts.next().and_then(|t| t.parse().ok());
+            let action = parts.next().and_then(Action::from_name);
+
+            match (time, action){
+                (Some(time), Some(action)) => replay.record(time, action),
+                _ => return Err(invalid(&format!("bad input line: {}", line))),
+            }
+        }
+
+        Ok(replay)
+    }
+
+    ///Starts feeding the recorded actions back
+    pub fn play(&self) -> Playback{
+        let mut inputs = self.inputs.clone();
+        inputs.reverse();
+        Playback {inputs: inputs}
+    }
+}
+
+pub struct Playback{
+    inputs: Vec<(u64, Action)>
+}
+
+impl Playback{
+    ///The time of the next recorded action, the simulation should not be
+    ///stepped past it
+    pub fn next_time(&self) -> Option<u64>{
+        self.inputs.last().map(|&(time, _)| time)
+    }
+
+    ///The next action if the simulation has reached its time
+    pub fn poll(&mut self, time: u64) -> Option<Action>{
+        match self.next_time(){
+            Some(next) if next <= time => self.inputs.pop().map(|(_, action)| action),
+            _ => None,
+        }
+    }
+}
+
+fn invalid(message: &str) -> io::Error{
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests{
+    use bindings::Action;
+    use simulation::Simulation;
+    use std::env;
+    use super::*;
+
+    #[test]
+    fn playback_reproduces_the_game(){
+        let mut setup = Setup::new(100, 30);
+        setup.set_difficulty(Difficulty::Custom, &Rules::parse("drain=1 ash_growth=4").unwrap());
+        let mut live = Simulation::new(7, setup);
+        let mut replay = Replay::new(7, setup);
+
+        for (i, action) in vec![Action::MoveLeft, Action::MoveDown, Action::MoveDown, Action::MoveRight].into_iter().enumerate(){
+            live.tick(700 + i as u64 * 130);
+            replay.record(live.elapsed(), action);
+            live.apply_input(action);
+        }
+        live.tick(2000);
+
+        let path = env::temp_dir().join("ash_dash_test.replay");
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path).unwrap();
+
+        let mut sim = Simulation::new(loaded.seed, loaded.setup);
+        let mut playback = loaded.play();
+        while let Some(next) = playback.next_time(){
+            sim.tick(next - sim.elapsed());
+            while let Some(action) = playback.poll(sim.elapsed()){
+                sim.apply_input(action);
+            }
+        }
+        sim.tick(live.elapsed() - sim.elapsed());
+
+        assert!(sim.game.player_pos == live.game.player_pos);
+        assert!(sim.game.items == live.game.items);
+        assert_eq!(sim.battery, live.battery);
+    }
+
+    #[test]
+    fn refuses_fields_too_big_to_play(){
+        let path = env::temp_dir().join("ash_dash_test_big.replay");
+        File::create(&path).unwrap().write_all(format!("{}\nseed 1\nsize 30000 30000\n", HEADER).as_bytes()).unwrap();
+
+        let err = Replay::load(&path).err().unwrap();
+        assert_eq!(err.to_string(), "the field is too big");
+    }
+}
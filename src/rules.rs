@@ -0,0 +1,226 @@
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Difficulty{
+    Easy,
+    Normal,
+    Hard,
+    Nightmare,
+    ///Normal, changed by the `rules.` lines of the config
+    Custom
+}
+
+pub const DIFFICULTIES: [Difficulty; 5] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard,
+    Difficulty::Nightmare, Difficulty::Custom];
+
+impl Difficulty{
+    ///The name used on the command line and in save files
+    pub fn name(&self) -> &'static str{
+        match *self{
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Nightmare => "nightmare",
+            Difficulty::Custom => "custom",
+        }
+    }
+
+    pub fn label(&self) -> &'static str{
+        match *self{
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+            Difficulty::Custom => "Custom",
+        }
+    }
+
+    ///The next harder difficulty, or the next easier one for a negative `step`, wrapping around
+    pub fn cycle(&self, step: isize) -> Difficulty{
+        let i = DIFFICULTIES.iter().position(|d| d == self).unwrap_or(0) as isize;
+        DIFFICULTIES[(i + step).rem_euclid(DIFFICULTIES.len() as isize) as usize]
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty>{
+        DIFFICULTIES.iter().find(|difficulty| difficulty.name() == name).cloned()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Mode{
+    ///Survive as long as the battery lasts
+    Classic,
+    ///The battery never drains, for practice. Runs do not make the high scores.
+    Zen
+}
+
+pub const MODES: [Mode; 2] = [Mode::Classic, Mode::Zen];
+
+impl Mode{
+    pub fn name(&self) -> &'static str{
+        match *self{
+            Mode::Classic => "classic",
+            Mode::Zen => "zen",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Mode>{
+        MODES.iter().find(|mode| mode.name() == name).cloned()
+    }
+}
+
+///The numbers behind a difficulty
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rules{
+    pub starting_charge: i16,
+    pub max_charge: i16,
+    ///Charge lost every second
+    pub drain: i16,
+    ///Charge gained from a `Powerup::Charge`
+    pub charge_bonus: i16,
+    ///How much the ash rate climbs every second
+    pub ash_growth: u16,
+    ///Powerups on the field when the game starts
+    pub start_charges: u16,
+    pub start_cleanups: u16,
+    pub start_resets: u16
+}
+
+pub const RULE_NAMES: [&str; 8] = ["starting_charge", "max_charge", "drain", "charge_bonus",
+    "ash_growth", "start_charges", "start_cleanups", "start_resets"];
+
+impl Rules{
+    pub fn preset(difficulty: Difficulty) -> Rules{
+        let (starting_charge, max_charge, drain, charge_bonus, ash_growth, charges, cleanups, resets) = match difficulty{
+            Difficulty::Easy => (15, 70, 1, 6, 1, 30, 12, 12),
+            Difficulty::Normal | Difficulty::Custom => (10, 70, 2, 5, 1, 25, 10, 10),
+            Difficulty::Hard => (10, 60, 2, 4, 2, 20, 8, 6),
+            Difficulty::Nightmare => (8, 50, 3, 4, 3, 15, 5, 3),
+        };
+
+        Rules {
+            starting_charge: starting_charge,
+            max_charge: max_charge,
+            drain: drain,
+            charge_bonus: charge_bonus,
+            ash_growth: ash_growth,
+            start_charges: charges,
+            start_cleanups: cleanups,
+            start_resets: resets
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<i64>{
+        match name{
+            "starting_charge" => Some(self.starting_charge as i64),
+            "max_charge" => Some(self.max_charge as i64),
+            "drain" => Some(self.drain as i64),
+            "charge_bonus" => Some(self.charge_bonus as i64),
+            "ash_growth" => Some(self.ash_growth as i64),
+            "start_charges" => Some(self.start_charges as i64),
+            "start_cleanups" => Some(self.start_cleanups as i64),
+            "start_resets" => Some(self.start_resets as i64),
+            _ => None,
+        }
+    }
+
+    ///Changes one rule by name. Every rule is a whole number from 0 to 1000,
+    ///the starting and max charge have to be at least 1.
+    pub fn set(&mut self, name: &str, value: i64) -> Result<(), String>{
+        let min = match name{
+            "starting_charge" | "max_charge" => 1,
+            _ => 0,
+        };
+        if value < min || value > 1000{
+            return Err(format!("{} must be from {} to 1000", name, min));
+        }
+
+        match name{
+            "starting_charge" => self.starting_charge = value as i16,
+            "max_charge" => self.max_charge = value as i16,
+            "drain" => self.drain = value as i16,
+            "charge_bonus" => self.charge_bonus = value as i16,
+            "ash_growth" => self.ash_growth = value as u16,
+            "start_charges" => self.start_charges = value as u16,
+            "start_cleanups" => self.start_cleanups = value as u16,
+            "start_resets" => self.start_resets = value as u16,
+            _ => return Err(format!("unknown rule {}", name)),
+        }
+        Ok(())
+    }
+
+    ///`name=value` for every rule, separated by spaces
+    pub fn describe(&self) -> String{
+        RULE_NAMES.iter()
+            .map(|name| format!("{}={}", name, self.get(name).unwrap_or(0)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    ///Reads what `describe` wrote, rules left out keep their normal values
+    pub fn parse(line: &str) -> Result<Rules, String>{
+        let mut rules = Rules::preset(Difficulty::Normal);
+
+        for pair in line.split_whitespace(){
+            let mut parts = pair.splitn(2, '=');
+            let name = parts.next().unwrap_or("");
+            let value = parts.next().and_then(|value| value.parse().ok())
+                .ok_or_else(|| format!("bad rule {}", pair))?;
+            rules.set(name, value)?;
+        }
+
+        Ok(rules)
+    }
+}
+
+///Everything that decides how a new game plays out, apart from the seed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Setup{
+    pub width: i16,
+    pub height: i16,
+    pub difficulty: Difficulty,
+    pub mode: Mode,
+    pub rules: Rules
+}
+
+impl Setup{
+    ///A classic game on normal
+    pub fn new(width: i16, height: i16) -> Setup{
+        Setup {
+            width: width,
+            height: height,
+            difficulty: Difficulty::Normal,
+            mode: Mode::Classic,
+            rules: Rules::preset(Difficulty::Normal)
+        }
+    }
+
+    ///Switches to the difficulty's preset, or to `custom` for a custom game
+    pub fn set_difficulty(&mut self, difficulty: Difficulty, custom: &Rules){
+        self.difficulty = difficulty;
+        self.rules = match difficulty{
+            Difficulty::Custom => *custom,
+            _ => Rules::preset(difficulty),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn rules_survive_a_round_trip(){
+        let mut rules = Rules::preset(Difficulty::Hard);
+        rules.set("drain", 7).unwrap();
+
+        assert_eq!(Rules::parse(&rules.describe()), Ok(rules));
+        assert!(rules.set("max_charge", 0).is_err());
+        assert!(rules.set("gravity", 1).is_err());
+    }
+
+    #[test]
+    fn difficulties_wrap_around(){
+        assert_eq!(Difficulty::Easy.cycle(-1), Difficulty::Custom);
+        assert_eq!(Difficulty::Custom.cycle(1), Difficulty::Easy);
+        assert_eq!(Difficulty::Normal.cycle(1), Difficulty::Hard);
+    }
+}
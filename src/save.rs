@@ -0,0 +1,366 @@
+use std::cmp;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use simulation::{Falling, Simulation, SavedState};
+use util::{data_dir, Position};
+use graphics::consts;
+use field::Field;
+use rules::{Difficulty, Mode, Rules, Setup};
+use volcano::{Phase, Volcano};
+use smoke::Smoke;
+use battery::Battery;
+use effects::{Effect, Effects, Status};
+use powerups;
+use super::Powerup;
+
+///Bump this whenever the layout below changes, old saves are then refused
+const VERSION: u32 = 11;
+
+pub fn path() -> PathBuf{
+    data_dir().join("save.txt")
+}
+
+pub fn exists() -> bool{
+    path().is_file()
+}
+
+pub fn delete(){
+    fs::remove_file(path()).ok();
+}
+
+pub fn save(sim: &Simulation) -> io::Result<()>{
+    fs::create_dir_all(data_dir())?;
+    write(sim, &mut File::create(path())?)
+}
+
+pub fn load() -> io::Result<Simulation>{
+    read(BufReader::new(File::open(path())?))
+}
+
+fn write<W: Write>(sim: &Simulation, file: &mut W) -> io::Result<()>{
+    let state = sim.save_state();
+
+    writeln!(file, "ashdash-save {}", VERSION)?;
+    writeln!(file, "seed {}", state.seed)?;
+    writeln!(file, "rng {}", state.rng_state)?;
+    writeln!(file, "elapsed {}", state.elapsed)?;
+    writeln!(file, "since_tick {}", state.since_tick)?;
+    writeln!(file, "since_flow {}", state.since_flow)?;
+    writeln!(file, "battery {} {} {} {}", state.battery.charge, state.battery.capacity, state.battery.lost,
+        state.battery.drain)?;
+    let effects: Vec<String> = state.effects.active().iter()
+        .map(|status| format!("{},{},{}", status.effect.name(), status.left, status.stacks))
+        .collect();
+    writeln!(file, "effects {}", effects.join(" "))?;
+    writeln!(file, "panels {}", state.panels)?;
+    writeln!(file, "ash_rate {}", state.ash_rate)?;
+    writeln!(file, "peak_ash_rate {}", state.peak_ash_rate)?;
+    writeln!(file, "powerups {}", state.powerups_collected)?;
+    writeln!(file, "player {} {}", state.player_pos.x, state.player_pos.y)?;
+    writeln!(file, "volcano {} {} {} {} {}", state.volcano.x, state.volcano.y, state.volcano.phase.name(),
+        state.volcano.left, state.volcano.big)?;
+    let falling: Vec<String> = state.falling.iter()
+        .map(|falling| format!("{},{},{}", falling.x, falling.y, falling.left))
+        .collect();
+    writeln!(file, "falling {}", falling.join(" "))?;
+    writeln!(file, "difficulty {}", state.setup.difficulty.name())?;
+    writeln!(file, "mode {}", state.setup.mode.name())?;
+    writeln!(file, "rules {}", state.setup.rules.describe())?;
+    writeln!(file, "field {} {}", state.items.width(), state.items.height())?;
+
+    for y in 0..state.items.height(){
+        let row: String = (0..state.items.width())
+            .map(|x| powerup_char(state.items.get(x, y)))
+            .collect();
+        writeln!(file, "{}", row)?;
+    }
+
+    writeln!(file, "ages")?;
+    for y in 0..state.items.height(){
+        let row: String = (0..state.items.width())
+            .map(|x| digit_char(state.items.age(x, y)))
+            .collect();
+        writeln!(file, "{}", row)?;
+    }
+
+    writeln!(file, "smoke {}", state.smoke.wind)?;
+    for y in 0..state.items.height(){
+        let row: String = (0..state.items.width())
+            .map(|x| digit_char(state.smoke.get(x, y)))
+            .collect();
+        writeln!(file, "{}", row)?;
+    }
+
+    Ok(())
+}
+
+fn read<R: BufRead>(reader: R) -> io::Result<Simulation>{
+    let lines: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
+    let mut lines = lines.iter();
+
+    if value(lines.next(), "ashdash-save")? != VERSION.to_string(){
+        return Err(invalid("unsupported save version"));
+    }
+
+    let seed = number(lines.next(), "seed")?;
+    let rng_state = number(lines.next(), "rng")?;
+    let elapsed = number(lines.next(), "elapsed")?;
+    let since_tick = number(lines.next(), "since_tick")?;
+    let since_flow = number(lines.next(), "since_flow")?;
+    let battery = battery(lines.next())?;
+    let effects = value(lines.next(), "effects")?.split_whitespace()
+        .map(status)
+        .collect::<io::Result<Vec<Status>>>()?;
+    let panels = number(lines.next(), "panels")?;
+    let ash_rate = number(lines.next(), "ash_rate")?;
+    let peak_ash_rate = number(lines.next(), "peak_ash_rate")?;
+    let powerups_collected = number(lines.next(), "powerups")?;
+    let player = pair(lines.next(), "player")?;
+    let volcano = volcano(lines.next())?;
+    let falling = value(lines.next(), "falling")?.split_whitespace()
+        .map(falling)
+        .collect::<io::Result<Vec<Falling>>>()?;
+    let difficulty = Difficulty::from_name(value(lines.next(), "difficulty")?)
+        .ok_or_else(|| invalid("unknown difficulty"))?;
+    let mode = Mode::from_name(value(lines.next(), "mode")?).ok_or_else(|| invalid("unknown mode"))?;
+    let rules = Rules::parse(value(lines.next(), "rules")?).map_err(|err| invalid(&err))?;
+
+    let (width, height) = pair(lines.next(), "field")?;
+    if width < 1 || height < 1{
+        return Err(invalid("the field is empty"));
+    }
+    if width > consts::MAX_FIELD_WIDTH || height > consts::MAX_FIELD_HEIGHT{
+        return Err(invalid("the field is too big"));
+    }
+    if player.0 < 0 || player.0 >= width || player.1 < 1 || player.1 > height{
+        return Err(invalid("the player is off the field"));
+    }
+
+    let mut items = Field::new(width, height);
+    for y in 0..height{
+        let row: Vec<char> = lines.next().ok_or_else(|| invalid("the field is cut short"))?.chars().collect();
+
+        if row.len() != width as usize{
+            return Err(invalid("a field row has the wrong width"));
+        }
+
+        for x in 0..width{
+            items.set(x, y, char_powerup(row[x as usize])?);
+        }
+    }
+
+    if lines.next().map(|line| &line[..]) != Some("ages"){
+        return Err(invalid("missing ages"));
+    }
+    for y in 0..height{
+        let row: Vec<char> = lines.next().ok_or_else(|| invalid("the ages are cut short"))?.chars().collect();
+
+        if row.len() != width as usize{
+            return Err(invalid("an ages row has the wrong width"));
+        }
+
+        for x in 0..width{
+            let age = row[x as usize].to_digit(36).ok_or_else(|| invalid("bad age"))?;
+            items.set_age(x, y, age as u8);
+        }
+    }
+
+    let mut smoke = Smoke::new(width, height);
+    smoke.wind = match value(lines.next(), "smoke")?{
+        "1" => 1,
+        "-1" => -1,
+        _ => return Err(invalid("bad wind")),
+    };
+    for y in 0..height{
+        let row: Vec<char> = lines.next().ok_or_else(|| invalid("the smoke is cut short"))?.chars().collect();
+
+        if row.len() != width as usize{
+            return Err(invalid("a smoke row has the wrong width"));
+        }
+
+        for x in 0..width{
+            let density = row[x as usize].to_digit(36).ok_or_else(|| invalid("bad smoke"))?;
+            smoke.set(x, y, density as u8);
+        }
+    }
+
+    Ok(Simulation::from_saved(SavedState {
+        player_pos: Position {x: player.0, y: player.1},
+        items: items,
+        volcano: volcano,
+        falling: falling,
+        smoke: smoke,
+        battery: battery,
+        effects: Effects::from_active(effects),
+        panels: panels as u16,
+        ash_rate: ash_rate as u16,
+        peak_ash_rate: peak_ash_rate as u16,
+        powerups_collected: powerups_collected as u16,
+        elapsed: elapsed,
+        since_tick: since_tick,
+        since_flow: since_flow,
+        seed: seed,
+        rng_state: rng_state,
+        setup: Setup {width: width, height: height, difficulty: difficulty, mode: mode, rules: rules}
+    }))
+}
+
+fn powerup_char(powerup: Option<Powerup>) -> char{
+    powerup.map_or('.', |powerup| powerups::kind(powerup).symbol)
+}
+
+///Ages and smoke as one base 36 digit, which is plenty for lava to cool
+fn digit_char(digit: u8) -> char{
+    ::std::char::from_digit(cmp::min(digit, 35) as u32, 36).unwrap_or('0')
+}
+
+fn char_powerup(c: char) -> io::Result<Option<Powerup>>{
+    match c{
+        '.' => Ok(None),
+        _ => powerups::from_symbol(c).map(Some).ok_or_else(|| invalid(&format!("unknown field cell: {}", c))),
+    }
+}
+
+///The rest of a `name value` line
+fn value<'a>(line: Option<&'a String>, name: &str) -> io::Result<&'a str>{
+    match line{
+        Some(line) if line.starts_with(name) && line[name.len()..].starts_with(' ') => Ok(line[name.len() + 1..].trim()),
+        _ => Err(invalid(&format!("missing {}", name))),
+    }
+}
+
+fn number(line: Option<&String>, name: &str) -> io::Result<u64>{
+    value(line, name)?.parse().map_err(|_| invalid(&format!("bad {}", name)))
+}
+
+fn pair(line: Option<&String>, name: &str) -> io::Result<(i16, i16)>{
+    let parts: Vec<i16> = value(line, name)?.split(' ').filter_map(|p| p.parse().ok()).collect();
+
+    match parts[..]{
+        [a, b] => Ok((a, b)),
+        _ => Err(invalid(&format!("bad {}", name))),
+    }
+}
+
+///`charge capacity lost drain`
+fn battery(line: Option<&String>) -> io::Result<Battery>{
+    let numbers: Vec<i16> = value(line, "battery")?.split(' ').filter_map(|n| n.parse().ok()).collect();
+
+    match numbers[..]{
+        [charge, capacity, lost, drain] => Ok(Battery {charge: charge, capacity: capacity, lost: lost, drain: drain}),
+        _ => Err(invalid("bad battery")),
+    }
+}
+
+fn volcano(line: Option<&String>) -> io::Result<Volcano>{
+    let parts: Vec<&str> = value(line, "volcano")?.split(' ').collect();
+    let bad = || invalid("bad volcano");
+
+    match parts[..]{
+        [x, y, phase, left, big] => Ok(Volcano {
+            x: x.parse().map_err(|_| bad())?,
+            y: y.parse().map_err(|_| bad())?,
+            phase: Phase::from_name(phase).ok_or_else(bad)?,
+            left: left.parse().map_err(|_| bad())?,
+            big: big.parse().map_err(|_| bad())?
+        }),
+        _ => Err(bad()),
+    }
+}
+
+///`x,y,left` of a cell of falling ash
+fn falling(part: &str) -> io::Result<Falling>{
+    let numbers: Vec<i64> = part.split(',').filter_map(|n| n.parse().ok()).collect();
+
+    match numbers[..]{
+        [x, y, left] if left >= 0 => Ok(Falling {x: x as i16, y: y as i16, left: left as u32}),
+        _ => Err(invalid("bad falling ash")),
+    }
+}
+
+///`name,left,stacks` of an effect on the player
+fn status(part: &str) -> io::Result<Status>{
+    let parts: Vec<&str> = part.split(',').collect();
+    let bad = || invalid("bad effect");
+
+    match parts[..]{
+        [name, left, stacks] => Ok(Status {
+            effect: Effect::from_name(name).ok_or_else(bad)?,
+            left: left.parse().map_err(|_| bad())?,
+            stacks: stacks.parse().map_err(|_| bad())?
+        }),
+        _ => Err(bad()),
+    }
+}
+
+fn invalid(message: &str) -> io::Error{
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests{
+    use simulation::Simulation;
+    use super::*;
+
+    #[test]
+    fn resumes_the_same_game(){
+        let mut sim = Simulation::new(99, Setup::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT));
+        sim.tick(4500);
+        sim.effects.add(Effect::Shield);
+        sim.effects.add(Effect::Shield);
+        sim.effects.add(Effect::Scorched);
+
+        let mut file = Vec::new();
+        write(&sim, &mut file).unwrap();
+        let mut resumed = read(&file[..]).unwrap();
+
+        sim.tick(2000);
+        resumed.tick(2000);
+        assert!(resumed.game.items == sim.game.items);
+        assert!(resumed.game.player_pos == sim.game.player_pos);
+        assert_eq!(resumed.volcano, sim.volcano);
+        assert_eq!(resumed.falling, sim.falling);
+        assert_eq!(resumed.smoke, sim.smoke);
+        assert_eq!(resumed.battery, sim.battery);
+        assert_eq!(resumed.effects, sim.effects);
+        assert_eq!(resumed.elapsed(), sim.elapsed());
+    }
+
+    #[test]
+    fn keeps_the_setup(){
+        let mut setup = Setup::new(90, 25);
+        setup.set_difficulty(Difficulty::Hard, &Rules::preset(Difficulty::Normal));
+        let sim = Simulation::new(7, setup);
+
+        let mut file = Vec::new();
+        write(&sim, &mut file).unwrap();
+        let resumed = read(&file[..]).unwrap();
+
+        assert_eq!(resumed.game.items.width(), 90);
+        assert_eq!(resumed.game.items.height(), 25);
+        assert!(resumed.setup() == setup);
+        assert!(resumed.game.items == sim.game.items);
+    }
+
+    #[test]
+    fn refuses_damaged_saves(){
+        let sim = Simulation::new(3, Setup::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT));
+        let mut file = Vec::new();
+        write(&sim, &mut file).unwrap();
+        let text = String::from_utf8(file).unwrap();
+
+        let off_field = text.replace(&format!("player {} {}", sim.game.player_pos.x, sim.game.player_pos.y),
+            &format!("player {} 0", consts::FIELD_WIDTH));
+        assert!(read(off_field.as_bytes()).is_err());
+
+        let huge = text.replace(&format!("field {} {}", consts::FIELD_WIDTH, consts::FIELD_HEIGHT), "field 30000 30000");
+        assert!(read(huge.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn refuses_other_versions(){
+        let file = b"ashdash-save 0\nseed 1\n";
+        assert!(read(&file[..]).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use std::cmp;
+use std::time::Duration;
+
+///Past this many missed ticks the game stops catching up and just carries
+///on from now, so a long stall does not fast forward the volcano
+const MAX_CATCH_UP: u32 = 10;
+
+///How often the simulation steps and how often the screen is flushed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rates{
+    pub ticks_per_second: u32,
+    pub frames_per_second: u32
+}
+
+impl Rates{
+    pub fn new() -> Rates{
+        Rates {ticks_per_second: 20, frames_per_second: 30}
+    }
+
+    ///Simulated milliseconds per tick
+    pub fn tick_millis(&self) -> u64{
+        1000 / cmp::max(self.ticks_per_second, 1) as u64
+    }
+
+    pub fn frame_millis(&self) -> u64{
+        1000 / cmp::max(self.frames_per_second, 1) as u64
+    }
+}
+
+impl Default for Rates{
+    fn default() -> Rates{
+        Rates::new()
+    }
+}
+
+///Drives the game loop at a fixed timestep. The simulation always moves in
+///whole ticks, late ticks are caught up on, and rendering runs on its own
+///clock. Times are in milliseconds.
+pub struct Scheduler{
+    tick_millis: u64,
+    frame_millis: u64,
+    next_tick: u64,
+    next_frame: u64
+}
+
+impl Scheduler{
+    pub fn new(rates: Rates, now: u64) -> Scheduler{
+        Scheduler {
+            tick_millis: rates.tick_millis(),
+            frame_millis: rates.frame_millis(),
+            next_tick: now + rates.tick_millis(),
+            next_frame: now
+        }
+    }
+
+    pub fn tick_millis(&self) -> u64{
+        self.tick_millis
+    }
+
+    ///How long input can be waited on before a tick or frame is due
+    pub fn timeout(&self, now: u64) -> Duration{
+        let next = cmp::min(self.next_tick, self.next_frame);
+        Duration::from_millis(next.saturating_sub(now))
+    }
+
+    ///How many ticks are due, including any that were missed
+    pub fn ticks(&mut self, now: u64) -> u32{
+        let mut ticks = 0;
+
+        while self.next_tick <= now{
+            if ticks == MAX_CATCH_UP{
+                self.next_tick = now + self.tick_millis;
+                break;
+            }
+
+            ticks += 1;
+            self.next_tick += self.tick_millis;
+        }
+
+        ticks
+    }
+
+    ///Whether the screen should be flushed. Missed frames are skipped, not
+    ///caught up on.
+    pub fn frame_due(&mut self, now: u64) -> bool{
+        if self.next_frame > now{
+            return false;
+        }
+
+        self.next_frame += self.frame_millis;
+        if self.next_frame <= now{
+            self.next_frame = now + self.frame_millis;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn catches_up_on_missed_ticks(){
+        let mut scheduler = Scheduler::new(Rates::new(), 0);
+
+        assert_eq!(scheduler.ticks(49), 0);
+        assert_eq!(scheduler.ticks(50), 1);
+        assert_eq!(scheduler.ticks(210), 3);
+        assert_eq!(scheduler.timeout(210), Duration::from_millis(0));
+    }
+
+    #[test]
+    fn gives_up_after_a_long_stall(){
+        let mut scheduler = Scheduler::new(Rates::new(), 0);
+
+        assert_eq!(scheduler.ticks(60000), MAX_CATCH_UP);
+        assert_eq!(scheduler.ticks(60049), 0);
+        assert_eq!(scheduler.ticks(60050), 1);
+    }
+
+    #[test]
+    fn frames_run_on_their_own_clock(){
+        let mut scheduler = Scheduler::new(Rates {ticks_per_second: 10, frames_per_second: 25}, 0);
+
+        assert!(scheduler.frame_due(0));
+        assert!(!scheduler.frame_due(39));
+        assert!(scheduler.frame_due(40));
+        assert_eq!(scheduler.timeout(40), Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,794 @@
+use rand::Rng;
+use bindings::Action;
+use random::GameRng;
+use rules::{Mode, Setup};
+use spawn::{self, Placement, SpawnTable};
+use util::Position;
+use std::cmp;
+use field::{Field, Ground};
+use lava::{self, FLOW_MILLIS};
+use volcano::{Volcano, FALLOUT_RADIUS};
+use smoke::Smoke;
+use powerups;
+use battery::Battery;
+use effects::{Effect, Effects};
+use super::{Game, Powerup};
+
+///How often the battery drains and the volcano spews more ash
+const TICK_MILLIS: u64 = 1000;
+
+///Powerups never spawn closer to the player than this many steps
+const SPAWN_DISTANCE: i16 = 3;
+///How many ticks falling ash is shown before it lands
+pub const ASH_WARNING_TICKS: u32 = 2;
+///A bonus powerup from `powerups::bonus_table` turns up this often, in ticks
+const BONUS_TICKS: u64 = 8;
+///How far a Magnet reaches and how far a Teleport takes you at least
+const MAGNET_RADIUS: i16 = 10;
+const TELEPORT_DISTANCE: i16 = 20;
+///Solar panels lying on the field at the start, and carried by the player
+const START_PANELS: u16 = 3;
+const CARRIED_PANELS: u16 = 2;
+///Percent chance each second that a panel under clear sky adds a unit of charge
+const PANEL_CHANCE: u32 = 25;
+
+///Things that happened during a step that the screen needs to know about
+pub enum Event{
+    PlayerMoved{from: Position},
+    Collected(Powerup),
+    ///Whatever was on the field cell is gone
+    Cleared{x: i16, y: i16},
+    ///A second has passed, so the field, charge and stats changed
+    Tick,
+    ///Things moved or turned up on the field, like flowing lava
+    FieldChanged,
+    ///A big eruption is this many seconds away, or starting at 0
+    EruptionWarning{seconds: u32},
+    ///Falling ash landed on the player
+    AshHit,
+    ///A shield took a magma hit in place of the battery
+    ShieldUsed,
+    ///The effect ran out of time
+    EffectEnded(Effect),
+    ///The player put down a solar panel, or had none left to put down
+    PanelPlaced,
+    NoPanels,
+    GameOver
+}
+
+///Ash on its way down, landing on the field cell `x, y` in `left` ticks
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Falling{
+    pub x: i16,
+    pub y: i16,
+    pub left: u32
+}
+
+///Everything needed to pick a game back up where it was left
+#[derive(PartialEq)]
+pub struct SavedState{
+    pub player_pos: Position,
+    pub items: Field,
+    pub volcano: Volcano,
+    pub falling: Vec<Falling>,
+    pub smoke: Smoke,
+    pub battery: Battery,
+    pub effects: Effects,
+    ///Solar panels the player can still put down
+    pub panels: u16,
+    pub ash_rate: u16,
+    pub peak_ash_rate: u16,
+    pub powerups_collected: u16,
+    pub elapsed: u64,
+    pub since_tick: u64,
+    pub since_flow: u64,
+    pub seed: u64,
+    pub rng_state: u64,
+    pub setup: Setup
+}
+
+///The rules of the game, without any drawing or input polling
+pub struct Simulation{
+    pub game: Game,
+    pub volcano: Volcano,
+    pub falling: Vec<Falling>,
+    pub smoke: Smoke,
+    pub battery: Battery,
+    pub effects: Effects,
+    ///Solar panels the player can still put down
+    pub panels: u16,
+    pub ash_rate: u16,
+    pub peak_ash_rate: u16,
+    pub powerups_collected: u16,
+    elapsed: u64,
+    since_tick: u64,
+    since_flow: u64,
+    over: bool,
+    seed: u64,
+    rng: GameRng,
+    setup: Setup
+}
+
+impl Simulation{
+    pub fn new(seed: u64, setup: Setup) -> Simulation{
+        let mut game = Game::new(setup.width, setup.height);
+        let mut rng = GameRng::new(seed);
+
+        let starting = [(Powerup::Charge, setup.rules.start_charges), (Powerup::Cleanup, setup.rules.start_cleanups),
+            (Powerup::Reset, setup.rules.start_resets)];
+        for &(powerup, count) in starting.iter(){
+            for _ in 0..count{
+                spawn_powerup(&mut game, &mut rng, &SpawnTable::only(powerup));
+            }
+        }
+        for _ in 0..START_PANELS{
+            spawn_powerup(&mut game, &mut rng, &SpawnTable::only(Powerup::Panel));
+        }
+
+        game.items.set(3, 3, Some(Powerup::Charge));
+        let volcano = Volcano::new(setup.width, setup.height, &mut rng);
+
+        Simulation {
+            game: game,
+            volcano: volcano,
+            falling: Vec::new(),
+            smoke: Smoke::new(setup.width, setup.height),
+            battery: Battery::new(setup.rules.starting_charge, setup.rules.max_charge, setup.rules.drain),
+            effects: Effects::new(),
+            panels: CARRIED_PANELS,
+            ash_rate: 0,
+            peak_ash_rate: 0,
+            powerups_collected: 0,
+            elapsed: 0,
+            since_tick: 0,
+            since_flow: 0,
+            over: false,
+            seed: seed,
+            rng: rng,
+            setup: setup
+        }
+    }
+
+    pub fn from_saved(state: SavedState) -> Simulation{
+        Simulation {
+            game: Game {player_pos: state.player_pos, items: state.items},
+            volcano: state.volcano,
+            falling: state.falling,
+            smoke: state.smoke,
+            battery: state.battery,
+            effects: state.effects,
+            panels: state.panels,
+            ash_rate: state.ash_rate,
+            peak_ash_rate: state.peak_ash_rate,
+            powerups_collected: state.powerups_collected,
+            elapsed: state.elapsed,
+            since_tick: state.since_tick,
+            since_flow: state.since_flow,
+            over: false,
+            seed: state.seed,
+            rng: GameRng::from_state(state.rng_state),
+            setup: state.setup
+        }
+    }
+
+    pub fn save_state(&self) -> SavedState{
+        SavedState {
+            player_pos: self.game.player_pos.clone(),
+            items: self.game.items.clone(),
+            volcano: self.volcano,
+            falling: self.falling.clone(),
+            smoke: self.smoke.clone(),
+            battery: self.battery,
+            effects: self.effects.clone(),
+            panels: self.panels,
+            ash_rate: self.ash_rate,
+            peak_ash_rate: self.peak_ash_rate,
+            powerups_collected: self.powerups_collected,
+            elapsed: self.elapsed,
+            since_tick: self.since_tick,
+            since_flow: self.since_flow,
+            seed: self.seed,
+            rng_state: self.rng.state(),
+            setup: self.setup
+        }
+    }
+
+    pub fn seed(&self) -> u64{
+        self.seed
+    }
+
+    pub fn setup(&self) -> Setup{
+        self.setup
+    }
+
+    ///Milliseconds of play so far
+    pub fn elapsed(&self) -> u64{
+        self.elapsed
+    }
+
+    pub fn is_over(&self) -> bool{
+        self.over
+    }
+
+    ///Moves the player and picks up whatever they end up standing on
+    pub fn apply_input(&mut self, action: Action) -> Vec<Event>{
+        let mut events = Vec::new();
+
+        if self.over{
+            return events;
+        }
+
+        let prev_player_pos = self.game.player_pos.clone();
+        let (width, height) = (self.game.items.width(), self.game.items.height());
+        match action{
+            Action::MoveLeft => self.game.player_pos.add_x(-1, width),
+            Action::MoveRight => self.game.player_pos.add_x(1, width),
+            Action::MoveUp => self.game.player_pos.add_y(-1, height),
+            Action::MoveDown => self.game.player_pos.add_y(1, height),
+            Action::Confirm => self.place_panel(&mut events),
+            _ => (),
+        }
+
+        if prev_player_pos != self.game.player_pos{
+            events.push(Event::PlayerMoved{from: prev_player_pos});
+        }
+
+        let x = self.game.player_pos.x;
+        let y = self.game.player_pos.y - 1;
+        if let Some(powerup) = self.game.items.get(x, y){
+            let kind = powerups::kind(powerup);
+            if kind.picked_up{
+                self.powerups_collected += 1;
+                self.game.items.set(x, y, None);
+                (kind.effect)(self, &mut events);
+                events.push(Event::Collected(powerup));
+            }
+        }
+
+        self.check_game_over(&mut events);
+        events
+    }
+
+    ///Moves the game on by `dt` milliseconds. Lava flows and ticks run in
+    ///the order they fall due, so how the time is split up does not matter.
+    pub fn tick(&mut self, dt: u64) -> Vec<Event>{
+        let mut events = Vec::new();
+        let mut left = dt;
+
+        while !self.over{
+            let to_flow = FLOW_MILLIS.saturating_sub(self.since_flow);
+            let to_tick = TICK_MILLIS.saturating_sub(self.since_tick);
+            let step = cmp::min(to_flow, to_tick);
+            if step > left{
+                self.advance(left);
+                break;
+            }
+            self.advance(step);
+            left -= step;
+
+            if step == to_flow{
+                self.since_flow -= FLOW_MILLIS;
+                if lava::flow(&mut self.game.items, &mut self.rng){
+                    events.push(Event::FieldChanged);
+                }
+            }
+            if step != to_tick{
+                continue;
+            }
+
+            self.since_tick -= TICK_MILLIS;
+            if self.setup.mode != Mode::Zen{
+                let scorched = if self.effects.has(Effect::Scorched) { 1 }else{ 0 };
+                self.battery.drain(scorched);
+            }
+            self.ash_rate = self.ash_rate.saturating_add(self.setup.rules.ash_growth);
+            self.peak_ash_rate = cmp::max(self.peak_ash_rate, self.ash_rate);
+
+            self.land_ash(&mut events);
+            if !self.effects.has(Effect::Frozen){
+                for _ in 0..self.volcano.ash_drops(self.ash_rate){
+                    if !self.drop_ash(){
+                        break;
+                    }
+                }
+            }
+            if self.effects.has(Effect::Magnet){
+                self.pull_charges(&mut events);
+            }
+            for effect in self.effects.tick(TICK_MILLIS){
+                events.push(Event::EffectEnded(effect));
+            }
+
+            self.expire_powerups(&mut events);
+            if ((self.elapsed - self.since_tick) / TICK_MILLIS).is_multiple_of(BONUS_TICKS){
+                spawn_powerup(&mut self.game, &mut self.rng, &powerups::bonus_table());
+            }
+            if let Some(seconds) = self.volcano.tick(&mut self.rng){
+                events.push(Event::EruptionWarning{seconds: seconds});
+            }
+            self.smoke.drift(&mut self.rng);
+            let (density, radius) = self.volcano.smoke();
+            self.smoke.puff(self.volcano.x, self.volcano.y, radius, density);
+            self.charge_panels();
+
+            events.push(Event::Tick);
+            self.check_game_over(&mut events);
+        }
+
+        events
+    }
+
+    fn advance(&mut self, dt: u64){
+        self.elapsed += dt;
+        self.since_tick += dt;
+        self.since_flow += dt;
+    }
+
+    ///Puts a new powerup on an empty cell away from the player
+    pub fn spawn(&mut self, powerup: Powerup){
+        spawn_powerup(&mut self.game, &mut self.rng, &SpawnTable::only(powerup));
+    }
+
+    ///A magma hit, which halves the charge, wears the battery and scorches it
+    ///unless a shield takes it
+    pub fn hurt(&mut self, events: &mut Vec<Event>){
+        if self.effects.consume(Effect::Shield){
+            events.push(Event::ShieldUsed);
+        }else{
+            self.battery.hit();
+            self.effects.add(Effect::Scorched);
+        }
+    }
+
+    ///Clears about two thirds of the ash on the field
+    pub fn clear_ash(&mut self, events: &mut Vec<Event>){
+        let field = &mut self.game.items;
+
+        for x in 0..field.width(){
+            for y in 0..field.height(){
+                if field.get(x, y) == Some(Powerup::Destroyed) && self.rng.gen_range(0, 100) <= 66{
+                    field.set(x, y, None);
+                    events.push(Event::Cleared{x: x, y: y});
+                }
+            }
+        }
+    }
+
+    ///Moves every ₽ within `MAGNET_RADIUS` of the player towards them, as
+    ///far as the empty cells in between allow. The closest ones go first.
+    pub fn pull_charges(&mut self, events: &mut Vec<Event>){
+        let player = (self.game.player_pos.x, self.game.player_pos.y - 1);
+        let field = &mut self.game.items;
+
+        let mut charges: Vec<(i16, i16)> = (player.0 - MAGNET_RADIUS..player.0 + MAGNET_RADIUS + 1)
+            .flat_map(|x| (player.1 - MAGNET_RADIUS..player.1 + MAGNET_RADIUS + 1).map(move |y| (x, y)))
+            .filter(|&(x, y)| field.get(x, y) == Some(Powerup::Charge))
+            .collect();
+        charges.sort_by_key(|&(x, y)| (x - player.0).abs() + (y - player.1).abs());
+
+        for (x, y) in charges{
+            let mut to = (x, y);
+            loop{
+                let (dx, dy) = (player.0 - to.0, player.1 - to.1);
+                let next = if dx.abs() >= dy.abs() { (to.0 + dx.signum(), to.1) }else{ (to.0, to.1 + dy.signum()) };
+                if next == player || !field.is_on(Ground::Empty, next.0, next.1){
+                    break;
+                }
+                to = next;
+            }
+
+            if to != (x, y){
+                field.set(x, y, None);
+                field.set(to.0, to.1, Some(Powerup::Charge));
+                events.push(Event::Cleared{x: x, y: y});
+                events.push(Event::FieldChanged);
+            }
+        }
+    }
+
+    ///Moves the player to an empty cell at least `TELEPORT_DISTANCE` away,
+    ///if there is one
+    pub fn teleport(&mut self, events: &mut Vec<Event>){
+        let from = self.game.player_pos.clone();
+        let placement = Placement::on(Ground::Empty).away_from((from.x, from.y - 1), TELEPORT_DISTANCE);
+
+        if let Some((x, y)) = spawn::find_cell(&self.game.items, &mut self.rng, placement){
+            self.game.player_pos = Position {x: x, y: y + 1};
+            events.push(Event::PlayerMoved{from: from});
+        }
+    }
+
+    ///Puts a solar panel down where the player stands, if they have one and
+    ///nothing is there yet
+    fn place_panel(&mut self, events: &mut Vec<Event>){
+        let (x, y) = (self.game.player_pos.x, self.game.player_pos.y - 1);
+
+        if self.panels == 0{
+            events.push(Event::NoPanels);
+        }else if self.game.items.is_on(Ground::Empty, x, y){
+            self.panels -= 1;
+            self.game.items.set(x, y, Some(Powerup::Panel));
+            events.push(Event::PanelPlaced);
+        }
+    }
+
+    ///Every panel under clear sky may trickle a unit of charge into the battery
+    fn charge_panels(&mut self){
+        for x in 0..self.game.items.width(){
+            for y in 0..self.game.items.height(){
+                if self.game.items.get(x, y) == Some(Powerup::Panel) && self.smoke.is_clear(x, y)
+                    && self.rng.gen_range(0, 100) < PANEL_CHANCE{
+                    self.battery.add(1);
+                }
+            }
+        }
+    }
+
+    ///Powerups with a lifetime get a second older, and fade once it is up
+    fn expire_powerups(&mut self, events: &mut Vec<Event>){
+        let field = &mut self.game.items;
+
+        for x in 0..field.width(){
+            for y in 0..field.height(){
+                let lifetime = field.get(x, y).and_then(|powerup| powerups::kind(powerup).lifetime);
+                if let Some(lifetime) = lifetime{
+                    let age = field.age(x, y) + 1;
+                    if age >= lifetime{
+                        field.set(x, y, None);
+                        events.push(Event::Cleared{x: x, y: y});
+                    }else{
+                        field.set_age(x, y, age);
+                    }
+                }
+            }
+        }
+    }
+
+    ///Picks where a cell of ash will land, mostly around the crater while the
+    ///volcano is busy. Returns false once there is nowhere left for ash to land.
+    fn drop_ash(&mut self) -> bool{
+        let anywhere = Placement::on(Ground::NotAsh);
+        let mut cell = None;
+
+        if self.rng.gen_range(0, 100) < self.volcano.fallout_chance(){
+            let crater = (self.volcano.x, self.volcano.y);
+            cell = spawn::find_cell(&self.game.items, &mut self.rng, anywhere.near(crater, FALLOUT_RADIUS));
+        }
+        match cell.or_else(|| spawn::find_cell(&self.game.items, &mut self.rng, anywhere)){
+            Some((x, y)) => {
+                //Already on its way, so this drop is lost
+                if !self.falling.iter().any(|falling| (falling.x, falling.y) == (x, y)){
+                    self.falling.push(Falling {x: x, y: y, left: ASH_WARNING_TICKS});
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    ///Brings falling ash a tick closer. Ash that lands on the player halves
+    ///their charge like walking into it would, and is gone.
+    fn land_ash(&mut self, events: &mut Vec<Event>){
+        let player = (self.game.player_pos.x, self.game.player_pos.y - 1);
+        let mut hit = false;
+
+        for falling in &mut self.falling{
+            falling.left = falling.left.saturating_sub(1);
+            if falling.left > 0{
+                continue;
+            }
+
+            if (falling.x, falling.y) == player{
+                hit = true;
+                self.game.items.set(falling.x, falling.y, None);
+            }else{
+                self.game.items.set(falling.x, falling.y, Some(Powerup::Destroyed));
+            }
+        }
+        self.falling.retain(|falling| falling.left > 0);
+
+        if hit{
+            events.push(Event::AshHit);
+            self.hurt(events);
+        }
+    }
+
+    fn check_game_over(&mut self, events: &mut Vec<Event>){
+        if self.battery.is_empty() && !self.over{
+            self.over = true;
+            events.push(Event::GameOver);
+        }
+    }
+}
+
+///Puts a powerup from the table on an empty cell away from the player. A
+///full field just gets nothing.
+fn spawn_powerup(game: &mut Game, rng: &mut GameRng, table: &SpawnTable){
+    let player = (game.player_pos.x, game.player_pos.y - 1);
+    let placement = Placement::on(Ground::Empty).away_from(player, SPAWN_DISTANCE);
+    spawn::spawn(&mut game.items, rng, table, placement);
+}
+
+#[cfg(test)]
+mod tests{
+    use bindings::Action;
+    use graphics::consts;
+    use smoke::MAX_DENSITY;
+    use super::*;
+
+    fn sim(seed: u64) -> Simulation{
+        Simulation::new(seed, Setup::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT))
+    }
+
+    ///Takes the solar panels off the field, so the charge only drains
+    fn without_panels(mut sim: Simulation) -> Simulation{
+        for x in 0..sim.game.items.width(){
+            for y in 0..sim.game.items.height(){
+                if sim.game.items.get(x, y) == Some(Powerup::Panel){
+                    sim.game.items.set(x, y, None);
+                }
+            }
+        }
+        sim
+    }
+
+    #[test]
+    fn drains_charge_every_second(){
+        let mut sim = without_panels(sim(1));
+        assert!(!sim.tick(999).iter().any(|event| matches!(*event, Event::Tick)));
+
+        let events = sim.tick(1);
+        assert!(events.iter().any(|event| matches!(*event, Event::Tick)));
+        assert_eq!(sim.battery.charge, 8);
+        assert_eq!(sim.ash_rate, 1);
+    }
+
+    #[test]
+    fn charge_is_capped(){
+        let mut sim = sim(1);
+        let max_charge = sim.setup().rules.max_charge;
+        sim.battery.charge = max_charge - 1;
+        sim.game.items.set(15, 14, Some(Powerup::Charge));
+        sim.apply_input(Action::Confirm);
+
+        assert_eq!(sim.battery.charge, max_charge);
+        assert_eq!(sim.powerups_collected, 1);
+    }
+
+    #[test]
+    fn game_ends_when_battery_is_empty(){
+        let mut sim = without_panels(sim(1));
+        let events = sim.tick(5000);
+
+        assert!(sim.is_over());
+        assert!(matches!(events.last(), Some(&Event::GameOver)));
+        assert!(sim.tick(1000).is_empty());
+    }
+
+    #[test]
+    fn zen_mode_never_drains(){
+        let mut setup = Setup::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT);
+        setup.mode = Mode::Zen;
+        let mut sim = without_panels(Simulation::new(1, setup));
+        for _ in 0..60{
+            sim.effects.add(Effect::Frozen);
+            sim.tick(1000);
+        }
+
+        assert_eq!(sim.battery.charge, 10);
+        assert!(!sim.is_over());
+    }
+
+    #[test]
+    fn player_stays_on_a_small_field(){
+        let mut sim = Simulation::new(3, Setup::new(20, 10));
+        sim.battery.charge = 50;
+        sim.game.player_pos = Position {x: 19, y: 10};
+        sim.apply_input(Action::MoveRight);
+        sim.apply_input(Action::MoveDown);
+
+        assert!(sim.game.player_pos == Position {x: 19, y: 10});
+    }
+
+    #[test]
+    fn a_field_full_of_ash_keeps_going(){
+        let mut setup = Setup::new(20, 10);
+        setup.mode = Mode::Zen;
+        let mut sim = Simulation::new(4, setup);
+        for x in 0..20{
+            for y in 0..10{
+                sim.game.items.set(x, y, Some(Powerup::Destroyed));
+            }
+        }
+        sim.tick(5000);
+
+        assert!(sim.falling.is_empty());
+        assert!(!sim.is_over());
+    }
+
+    #[test]
+    fn splitting_time_differently_plays_the_same_game(){
+        let mut setup = Setup::new(consts::FIELD_WIDTH, consts::FIELD_HEIGHT);
+        setup.mode = Mode::Zen;
+        let mut coarse = Simulation::new(7, setup);
+        let mut fine = Simulation::new(7, setup);
+        for _ in 0..10{
+            coarse.tick(1500);
+        }
+        for _ in 0..30{
+            fine.tick(500);
+        }
+
+        assert!(coarse.save_state() == fine.save_state());
+    }
+
+    #[test]
+    fn ash_is_never_queued_twice_on_a_cell(){
+        let mut setup = Setup::new(20, 10);
+        setup.mode = Mode::Zen;
+        let mut sim = Simulation::new(4, setup);
+        for x in 0..20{
+            for y in 0..10{
+                if x > 2 || y > 0{
+                    sim.game.items.set(x, y, Some(Powerup::Destroyed));
+                }
+            }
+        }
+
+        for _ in 0..30{
+            sim.tick(1000);
+            for (i, falling) in sim.falling.iter().enumerate(){
+                assert!(sim.falling[i + 1..].iter().all(|other| (other.x, other.y) != (falling.x, falling.y)));
+            }
+        }
+    }
+
+    #[test]
+    fn the_ash_rate_stops_at_its_limit(){
+        let mut setup = Setup::new(20, 10);
+        setup.mode = Mode::Zen;
+        setup.rules.ash_growth = 1000;
+        let mut sim = Simulation::new(4, setup);
+        for _ in 0..70{
+            sim.effects.add(Effect::Frozen);
+            sim.tick(1000);
+        }
+
+        assert_eq!(sim.ash_rate, u16::MAX);
+        assert_eq!(sim.peak_ash_rate, u16::MAX);
+    }
+
+    #[test]
+    fn ash_is_announced_before_it_lands(){
+        let mut sim = sim(1);
+        sim.falling.push(Falling {x: 40, y: 20, left: ASH_WARNING_TICKS});
+        sim.tick(1000);
+        assert_eq!(sim.game.items.get(40, 20), None);
+        assert!(sim.falling.iter().all(|falling| falling.left > 0));
+
+        sim.tick(1000);
+        assert_eq!(sim.game.items.get(40, 20), Some(Powerup::Destroyed));
+    }
+
+    #[test]
+    fn landing_ash_hurts_the_player(){
+        let mut sim = sim(1);
+        sim.battery.charge = 42;
+        sim.falling.push(Falling {x: 15, y: 14, left: 1});
+        let events = sim.tick(1000);
+
+        assert_eq!(sim.battery.charge, 20);
+        assert!(events.iter().any(|event| matches!(*event, Event::AshHit)));
+        assert!(sim.battery.capacity < sim.setup().rules.max_charge);
+        assert!(sim.effects.has(Effect::Scorched));
+    }
+
+    #[test]
+    fn scorched_drains_faster_until_it_wears_off(){
+        let mut sim = without_panels(sim(1));
+        sim.battery.charge = 40;
+        sim.effects.add(Effect::Scorched);
+        let mut ended = false;
+        for _ in 0..Effect::Scorched.millis() / TICK_MILLIS{
+            ended = sim.tick(1000).iter().any(|event| matches!(*event, Event::EffectEnded(Effect::Scorched)));
+        }
+
+        assert_eq!(sim.battery.charge, 25);
+        assert!(ended);
+        assert!(!sim.effects.has(Effect::Scorched));
+    }
+
+    #[test]
+    fn a_shield_takes_the_hit(){
+        let mut sim = sim(1);
+        sim.battery.charge = 40;
+        sim.game.items.set(15, 14, Some(Powerup::Shield));
+        sim.apply_input(Action::Confirm);
+        sim.game.items.set(15, 15, Some(Powerup::Destroyed));
+        let events = sim.apply_input(Action::MoveDown);
+
+        assert_eq!(sim.battery.charge, 40);
+        assert!(!sim.effects.has(Effect::Shield));
+        assert!(events.iter().any(|event| matches!(*event, Event::ShieldUsed)));
+    }
+
+    #[test]
+    fn freeze_stops_the_ash(){
+        let mut sim = sim(1);
+        sim.battery.charge = 60;
+        sim.game.items.set(15, 14, Some(Powerup::Freeze));
+        sim.apply_input(Action::Confirm);
+        sim.tick(5000);
+
+        assert!(sim.falling.is_empty());
+        assert!(sim.effects.has(Effect::Frozen));
+    }
+
+    #[test]
+    fn magnet_pulls_charges_in(){
+        let mut sim = sim(1);
+        sim.game.items.set(15, 14, Some(Powerup::Magnet));
+        sim.game.items.set(22, 14, None);
+        sim.game.items.set(23, 14, Some(Powerup::Charge));
+        for x in 16..23{
+            sim.game.items.set(x, 14, None);
+        }
+        sim.apply_input(Action::Confirm);
+
+        assert_eq!(sim.game.items.get(23, 14), None);
+        assert_eq!(sim.game.items.get(16, 14), Some(Powerup::Charge));
+    }
+
+    #[test]
+    fn teleport_goes_far(){
+        let mut sim = sim(1);
+        sim.game.items.set(15, 14, Some(Powerup::Teleport));
+        sim.apply_input(Action::Confirm);
+        let moved = (sim.game.player_pos.x - 15).abs() + (sim.game.player_pos.y - 15).abs();
+
+        assert!(moved >= TELEPORT_DISTANCE);
+    }
+
+    #[test]
+    fn panels_charge_under_clear_sky(){
+        let mut sim = without_panels(sim(1));
+        sim.battery.charge = 30;
+        sim.apply_input(Action::Confirm);
+        assert_eq!(sim.game.items.get(15, 14), Some(Powerup::Panel));
+        assert_eq!(sim.panels, CARRIED_PANELS - 1);
+
+        for _ in 0..20{
+            sim.charge_panels();
+        }
+        assert!(sim.battery.charge > 30);
+
+        let charge = sim.battery.charge;
+        sim.smoke.puff(15, 14, 0, MAX_DENSITY);
+        for _ in 0..20{
+            sim.charge_panels();
+        }
+        assert_eq!(sim.battery.charge, charge);
+    }
+
+    #[test]
+    fn rock_is_safe_to_walk_on(){
+        let mut sim = sim(1);
+        sim.battery.charge = 40;
+        sim.game.items.set(15, 14, Some(Powerup::Rock));
+        sim.apply_input(Action::Confirm);
+
+        assert_eq!(sim.battery.charge, 40);
+        assert_eq!(sim.powerups_collected, 0);
+        assert_eq!(sim.game.items.get(15, 14), Some(Powerup::Rock));
+    }
+
+    #[test]
+    fn same_seed_same_game(){
+        let mut a = sim(42);
+        let mut b = sim(42);
+        a.tick(3000);
+        b.tick(3000);
+
+        assert!(a.game.items == b.game.items);
+    }
+}
@@ -0,0 +1,108 @@
+use std::cmp;
+use rand::Rng;
+use random::GameRng;
+
+///The thickest smoke gets
+pub const MAX_DENSITY: u8 = 9;
+///Smoke thinner than this still lets the sun through
+pub const CLEAR_DENSITY: u8 = 2;
+///One in this many seconds the wind turns round
+const WIND_CHANGE_ODDS: u32 = 20;
+///One in this many seconds each cell of smoke thins out a little
+const THIN_ODDS: u32 = 4;
+
+///The smoke hanging over the field, one density per cell. The volcano puffs
+///it out and the wind blows it sideways, spreading it up and down as it goes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Smoke{
+    width: i16,
+    height: i16,
+    density: Vec<u8>,
+    ///Which way the smoke drifts, 1 for right and -1 for left
+    pub wind: i16
+}
+
+impl Smoke{
+    pub fn new(width: i16, height: i16) -> Smoke{
+        Smoke {width: width, height: height, density: vec![0; width as usize * height as usize], wind: 1}
+    }
+
+    ///Clear sky outside the field
+    pub fn get(&self, x: i16, y: i16) -> u8{
+        if x >= 0 && y >= 0 && x < self.width && y < self.height{
+            self.density[x as usize * self.height as usize + y as usize]
+        }else{
+            0
+        }
+    }
+
+    ///Writes outside the field are ignored
+    pub fn set(&mut self, x: i16, y: i16, density: u8){
+        if x >= 0 && y >= 0 && x < self.width && y < self.height{
+            self.density[x as usize * self.height as usize + y as usize] = cmp::min(density, MAX_DENSITY);
+        }
+    }
+
+    ///Whether the sun reaches the cell `x, y`
+    pub fn is_clear(&self, x: i16, y: i16) -> bool{
+        self.get(x, y) < CLEAR_DENSITY
+    }
+
+    ///Thickens the smoke on the cells within `radius` of `x, y`
+    pub fn puff(&mut self, x: i16, y: i16, radius: i16, amount: u8){
+        for px in x - radius..x + radius + 1{
+            for py in y - radius..y + radius + 1{
+                let density = self.get(px, py).saturating_add(amount);
+                self.set(px, py, density);
+            }
+        }
+    }
+
+    ///Moves the smoke on by a second. Every cell takes most of its smoke
+    ///from the cell upwind and some from the ones above and below that,
+    ///so smoke blown off the field is gone.
+    pub fn drift(&mut self, rng: &mut GameRng){
+        if rng.gen_range(0, WIND_CHANGE_ODDS) == 0{
+            self.wind = -self.wind;
+        }
+
+        let mut next = vec![0; self.density.len()];
+        for x in 0..self.width{
+            for y in 0..self.height{
+                let from = x - self.wind;
+                let sum = 2 * self.get(from, y) as u32 + self.get(from, y - 1) as u32 + self.get(from, y + 1) as u32
+                    + self.get(x, y) as u32;
+                let mut density = ((sum + 2) / 5) as u8;
+                if density > 0 && rng.gen_range(0, THIN_ODDS) == 0{
+                    density -= 1;
+                }
+                next[x as usize * self.height as usize + y as usize] = density;
+            }
+        }
+        self.density = next;
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn drifts_with_the_wind_and_thins_out(){
+        let mut smoke = Smoke::new(30, 9);
+        let mut rng = GameRng::new(5);
+        smoke.wind = 1;
+        smoke.puff(5, 4, 1, MAX_DENSITY);
+        assert!(!smoke.is_clear(5, 4));
+
+        smoke.drift(&mut rng);
+        assert_eq!(smoke.wind, 1);
+        assert!(smoke.get(7, 4) > 0);
+        assert_eq!(smoke.get(3, 4), 0);
+
+        for _ in 0..60{
+            smoke.drift(&mut rng);
+        }
+        assert!((0..30).all(|x| (0..9).all(|y| smoke.is_clear(x, y))));
+    }
+}
@@ -0,0 +1,180 @@
+use rand::Rng;
+use field::{Field, Ground};
+use random::GameRng;
+use Powerup;
+
+///Random picks that may land too close to the player before every cell is checked
+const DISTANCE_TRIES: u32 = 8;
+
+///Which powerups can spawn and how likely each is, by weight
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpawnTable{
+    entries: Vec<(Powerup, u32)>
+}
+
+impl SpawnTable{
+    pub fn new(entries: &[(Powerup, u32)]) -> SpawnTable{
+        SpawnTable {entries: entries.to_vec()}
+    }
+
+    ///A table that always spawns `powerup`
+    pub fn only(powerup: Powerup) -> SpawnTable{
+        SpawnTable::new(&[(powerup, 1)])
+    }
+
+    ///A random powerup from the table, `None` if every weight is zero
+    pub fn pick(&self, rng: &mut GameRng) -> Option<Powerup>{
+        let total: u32 = self.entries.iter().map(|&(_, weight)| weight).sum();
+        if total == 0{
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0, total);
+        for &(powerup, weight) in &self.entries{
+            if roll < weight{
+                return Some(powerup);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+///Where a spawn is allowed to land
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Placement{
+    pub ground: Ground,
+    ///A field cell to keep away from, and how many steps away
+    pub away_from: Option<((i16, i16), i16)>,
+    ///A field cell to stay close to, and at most how many steps away
+    pub near: Option<((i16, i16), i16)>
+}
+
+impl Placement{
+    pub fn on(ground: Ground) -> Placement{
+        Placement {ground: ground, away_from: None, near: None}
+    }
+
+    ///Only cells at least `distance` steps from `cell`
+    pub fn away_from(self, cell: (i16, i16), distance: i16) -> Placement{
+        Placement {away_from: Some((cell, distance)), ..self}
+    }
+
+    ///Only cells at most `distance` steps from `cell`
+    pub fn near(self, cell: (i16, i16), distance: i16) -> Placement{
+        Placement {near: Some((cell, distance)), ..self}
+    }
+
+    fn allows(&self, cell: (i16, i16)) -> bool{
+        self.away_from.is_none_or(|(from, distance)| steps(cell, from) >= distance) &&
+            self.near.is_none_or(|(to, distance)| steps(cell, to) <= distance)
+    }
+}
+
+///A random cell allowed by the placement, or `None` when there is no room left
+pub fn find_cell(field: &Field, rng: &mut GameRng, placement: Placement) -> Option<(i16, i16)>{
+    if let Some(((x, y), distance)) = placement.near{
+        //Only a small patch to look through, so check all of it
+        let cells: Vec<(i16, i16)> = (x - distance..x + distance + 1)
+            .flat_map(|x| (y - distance..y + distance + 1).map(move |y| (x, y)))
+            .filter(|&(x, y)| field.is_on(placement.ground, x, y) && placement.allows((x, y)))
+            .collect();
+        return pick(rng, &cells);
+    }
+
+    let count = field.count(placement.ground);
+    if count == 0{
+        return None;
+    }
+    if placement.away_from.is_none(){
+        return Some(field.nth(placement.ground, rng.gen_range(0, count)));
+    }
+
+    for _ in 0..DISTANCE_TRIES{
+        let cell = field.nth(placement.ground, rng.gen_range(0, count));
+        if placement.allows(cell){
+            return Some(cell);
+        }
+    }
+
+    let cells: Vec<(i16, i16)> = (0..count)
+        .map(|n| field.nth(placement.ground, n))
+        .filter(|&cell| placement.allows(cell))
+        .collect();
+    pick(rng, &cells)
+}
+
+fn pick(rng: &mut GameRng, cells: &[(i16, i16)]) -> Option<(i16, i16)>{
+    if cells.is_empty(){
+        None
+    }else{
+        Some(cells[rng.gen_range(0, cells.len())])
+    }
+}
+
+fn steps(a: (i16, i16), b: (i16, i16)) -> i16{
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+///Puts a powerup from the table somewhere the placement allows. Gives back
+///where it went, or `None` if nothing could be spawned.
+pub fn spawn(field: &mut Field, rng: &mut GameRng, table: &SpawnTable, placement: Placement) -> Option<(i16, i16)>{
+    let powerup = table.pick(rng)?;
+    let (x, y) = find_cell(field, rng, placement)?;
+
+    field.set(x, y, Some(powerup));
+    Some((x, y))
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn a_full_field_spawns_nothing(){
+        let mut field = Field::new(2, 2);
+        let mut rng = GameRng::new(1);
+
+        for _ in 0..4{
+            assert!(spawn(&mut field, &mut rng, &SpawnTable::only(Powerup::Charge), Placement::on(Ground::Empty)).is_some());
+        }
+        assert_eq!(spawn(&mut field, &mut rng, &SpawnTable::only(Powerup::Reset), Placement::on(Ground::Empty)), None);
+        assert_eq!(field.count(Ground::NotAsh), 4);
+    }
+
+    #[test]
+    fn keeps_away_from_the_player(){
+        let mut field = Field::new(10, 10);
+        let mut rng = GameRng::new(2);
+        let placement = Placement::on(Ground::Empty).away_from((0, 0), 18);
+
+        assert_eq!(spawn(&mut field, &mut rng, &SpawnTable::only(Powerup::Charge), placement), Some((9, 9)));
+        assert_eq!(spawn(&mut field, &mut rng, &SpawnTable::only(Powerup::Charge), placement), None);
+    }
+
+    #[test]
+    fn stays_near_a_cell(){
+        let mut field = Field::new(10, 10);
+        let mut rng = GameRng::new(4);
+        let placement = Placement::on(Ground::NotAsh).near((0, 0), 1);
+
+        for _ in 0..3{
+            let (x, y) = find_cell(&field, &mut rng, placement).unwrap();
+            assert!(x + y <= 1);
+            field.set(x, y, Some(Powerup::Destroyed));
+        }
+        assert_eq!(find_cell(&field, &mut rng, placement), None);
+    }
+
+    #[test]
+    fn follows_the_weights(){
+        let mut rng = GameRng::new(3);
+        let table = SpawnTable::new(&[(Powerup::Charge, 3), (Powerup::Reset, 1), (Powerup::Cleanup, 0)]);
+        let picks: Vec<Powerup> = (0..400).filter_map(|_| table.pick(&mut rng)).collect();
+        let charges = picks.iter().filter(|&&powerup| powerup == Powerup::Charge).count();
+
+        assert!(charges > 250 && charges < 350);
+        assert!(!picks.contains(&Powerup::Cleanup));
+        assert_eq!(SpawnTable::new(&[]).pick(&mut rng), None);
+    }
+}
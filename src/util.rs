@@ -1,4 +1,6 @@
-use super::graphics::consts;
+use std::env;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Clone, PartialEq)]
 pub struct Position{
@@ -8,10 +10,11 @@ pub struct Position{
 
 impl Position {
 
-    pub fn add_x(&mut self, val: i16){
+    ///Keeps the player inside a field `width` cells wide
+    pub fn add_x(&mut self, val: i16, width: i16){
         self.x += val;
 
-        while self.x > consts::WORLD_WIDTH - 1{
+        while self.x > width - 1{
             self.x -= 1;
         }
 
@@ -21,10 +24,11 @@ impl Position {
         }
     }
 
-    pub fn add_y(&mut self, val: i16){
+    ///The player's y is one below the field row they stand on, so this keeps it in 1..=height
+    pub fn add_y(&mut self, val: i16, height: i16){
         self.y += val;
 
-        while self.y > consts::WORLD_HEIGHT - consts::STATUS_BAR_HEIGHT - 1{
+        while self.y > height{
             self.y -= 1;
         }
 
@@ -34,3 +38,40 @@ impl Position {
     }
 
 }
+
+///Where the game keeps its high scores, saves and config
+pub fn data_dir() -> PathBuf{
+    if cfg!(windows){
+        if let Some(app_data) = env::var_os("APPDATA"){
+            return PathBuf::from(app_data).join("AshDash");
+        }
+    }
+
+    if let Some(data_home) = env::var_os("XDG_DATA_HOME"){
+        return PathBuf::from(data_home).join("ash-dash");
+    }
+
+    match env::var_os("HOME"){
+        Some(home) => PathBuf::from(home).join(".local").join("share").join("ash-dash"),
+        None => PathBuf::from("."),
+    }
+}
+
+///Today's date as YYYY-MM-DD (UTC)
+pub fn today() -> String{
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let days = (seconds / 86400) as i64;
+
+    //Howard Hinnant's days to civil date algorithm
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,178 @@
+use rand::Rng;
+use random::GameRng;
+
+///How far from the crater most of the fallout lands during an eruption
+pub const FALLOUT_RADIUS: i16 = 8;
+///How many seconds ahead a big eruption is announced
+const WARNING_SECONDS: u32 = 3;
+///One in this many eruptions is a big one
+const BIG_ODDS: u32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Phase{
+    Dormant,
+    Rumbling,
+    Erupting,
+    Cooling
+}
+
+pub const PHASES: [Phase; 4] = [Phase::Dormant, Phase::Rumbling, Phase::Erupting, Phase::Cooling];
+
+impl Phase{
+    pub fn name(&self) -> &'static str{
+        match *self{
+            Phase::Dormant => "dormant",
+            Phase::Rumbling => "rumbling",
+            Phase::Erupting => "erupting",
+            Phase::Cooling => "cooling",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Phase>{
+        PHASES.iter().find(|phase| phase.name() == name).cloned()
+    }
+
+    fn next(&self) -> Phase{
+        match *self{
+            Phase::Dormant => Phase::Rumbling,
+            Phase::Rumbling => Phase::Erupting,
+            Phase::Erupting => Phase::Cooling,
+            Phase::Cooling => Phase::Dormant,
+        }
+    }
+
+    fn seconds(&self) -> u32{
+        match *self{
+            Phase::Dormant => 10,
+            Phase::Rumbling => 8,
+            Phase::Erupting => 6,
+            Phase::Cooling => 8,
+        }
+    }
+}
+
+///Kuklonfusta. It goes round its phases every half minute or so, and how
+///much ash actually falls depends on the phase.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Volcano{
+    ///The crater, in field cells
+    pub x: i16,
+    pub y: i16,
+    pub phase: Phase,
+    ///Whole seconds until the next phase
+    pub left: u32,
+    ///Whether the coming or current eruption is a big one
+    pub big: bool
+}
+
+impl Volcano{
+    ///Somewhere along the top of the field, so its lava has room to run downhill
+    pub fn new(width: i16, height: i16, rng: &mut GameRng) -> Volcano{
+        Volcano {
+            x: rng.gen_range(width / 4, width - width / 4),
+            y: rng.gen_range(0, height / 4 + 1),
+            phase: Phase::Dormant,
+            left: Phase::Dormant.seconds(),
+            big: false
+        }
+    }
+
+    ///How many cells of ash fall this second when the ash rate is `ash_rate`
+    pub fn ash_drops(&self, ash_rate: u16) -> u16{
+        let percent = match self.phase{
+            Phase::Dormant => 25,
+            Phase::Rumbling => 75,
+            Phase::Erupting if self.big => 400,
+            Phase::Erupting => 250,
+            Phase::Cooling => 100,
+        };
+        (ash_rate as u32 * percent / 100).min(u16::MAX as u32) as u16
+    }
+
+    ///How thick the smoke it puffs out this second is, and how far around
+    ///the crater it reaches
+    pub fn smoke(&self) -> (u8, i16){
+        match self.phase{
+            Phase::Dormant => (1, 0),
+            Phase::Rumbling => (3, 1),
+            Phase::Erupting if self.big => (9, 3),
+            Phase::Erupting => (9, 2),
+            Phase::Cooling => (4, 1),
+        }
+    }
+
+    ///Percent of the ash that lands within `FALLOUT_RADIUS` of the crater
+    pub fn fallout_chance(&self) -> u32{
+        match self.phase{
+            Phase::Erupting => 70,
+            Phase::Rumbling | Phase::Cooling => 40,
+            Phase::Dormant => 10,
+        }
+    }
+
+    ///Moves on by a second. Gives back how many seconds are left when a big
+    ///eruption is coming, and 0 as it starts.
+    pub fn tick(&mut self, rng: &mut GameRng) -> Option<u32>{
+        self.left = self.left.saturating_sub(1);
+
+        if self.left == 0{
+            self.phase = self.phase.next();
+            self.left = self.phase.seconds();
+            if self.phase == Phase::Rumbling{
+                self.big = rng.gen_range(0, BIG_ODDS) == 0;
+            }
+            if self.phase == Phase::Erupting && self.big{
+                return Some(0);
+            }
+        }
+
+        if self.phase == Phase::Rumbling && self.big && self.left <= WARNING_SECONDS{
+            Some(self.left)
+        }else{
+            None
+        }
+    }
+
+    ///Sends the volcano back to sleep
+    pub fn calm(&mut self){
+        self.phase = Phase::Dormant;
+        self.left = Phase::Dormant.seconds();
+        self.big = false;
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn warns_before_a_big_eruption(){
+        let mut rng = GameRng::new(1);
+        let mut volcano = Volcano::new(140, 34, &mut rng);
+        let mut warnings = Vec::new();
+
+        while !(volcano.phase == Phase::Erupting && volcano.big){
+            if let Some(seconds) = volcano.tick(&mut rng){
+                warnings.push(seconds);
+            }
+        }
+
+        assert_eq!(&warnings[warnings.len() - 4..], &[3, 2, 1, 0]);
+        assert!(volcano.ash_drops(10) > 25);
+        assert_eq!(volcano.ash_drops(u16::MAX), u16::MAX);
+    }
+
+    #[test]
+    fn calming_puts_it_to_sleep(){
+        let mut rng = GameRng::new(2);
+        let mut volcano = Volcano::new(140, 34, &mut rng);
+        for _ in 0..12{
+            volcano.tick(&mut rng);
+        }
+        assert_eq!(volcano.phase, Phase::Rumbling);
+
+        volcano.calm();
+        assert_eq!(volcano.phase, Phase::Dormant);
+        assert_eq!(volcano.ash_drops(8), 2);
+    }
+}